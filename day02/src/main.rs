@@ -1,4 +1,9 @@
-use std::{cmp::Ordering::Equal, fs};
+mod repair;
+mod safety_policy;
+
+use repair::minimal_repair;
+use safety_policy::{AllowedDirection, SafetyPolicy};
+use std::{env, fs};
 
 fn main() {
     let input = fs::read_to_string("inputs/day02.txt").unwrap();
@@ -13,88 +18,88 @@ fn main() {
         );
     }
 
-    part1(puzzle_data.clone());
-    part2(puzzle_data);
+    part1(&puzzle_data);
+    part2(&puzzle_data);
+    repairs(&puzzle_data);
+
+    // Other sensor fleets start from the part 2 preset and override its rules
+    let mut fleet_policy = None;
+    for arg in env::args().skip(1) {
+        let policy = fleet_policy.get_or_insert_with(SafetyPolicy::part_2);
+
+        if let Some(direction) = arg.strip_prefix("--direction=") {
+            policy.direction = AllowedDirection::from(direction);
+        } else if let Some(step) = arg.strip_prefix("--min-step=") {
+            policy.min_step = step.parse().unwrap();
+        } else if let Some(step) = arg.strip_prefix("--max-step=") {
+            policy.max_step = step.parse().unwrap();
+        } else if let Some(tolerance) = arg.strip_prefix("--tolerance=") {
+            policy.tolerance = tolerance.parse().unwrap();
+        } else if arg == "--plateaus" {
+            policy.allow_plateaus = true;
+        } else {
+            panic!("Unknown option {}", arg);
+        }
+    }
+
+    if let Some(policy) = fleet_policy {
+        fleet(&puzzle_data, &policy);
+    }
 }
 
-fn part1(puzzle_data: Vec<Vec<isize>>) {
+fn part1(puzzle_data: &[Vec<isize>]) {
     println!("Day 02 - Part 1");
 
-    let mut safe_reports = 0;
-    let mut analyzed_reports = 0;
-    'report_loop: for report in puzzle_data {
-        analyzed_reports += 1;
-
-        let mut levels = report.iter();
-        let first_level = levels.next().unwrap();
-        let second_level = levels.next().unwrap();
-
-        let ordering = second_level.cmp(first_level);
-        if ordering == Equal || (second_level - first_level).abs() > 3 {
-            continue;
-        }
+    count_safe_reports(puzzle_data, &SafetyPolicy::part_1());
+}
 
-        let mut last_level = second_level;
-        for level in levels {
-            if level.cmp(last_level) != ordering || (level - last_level).abs() > 3 {
-                continue 'report_loop;
-            }
+fn part2(puzzle_data: &[Vec<isize>]) {
+    println!("Day 02 - Part 2");
 
-            last_level = level;
-        }
+    count_safe_reports(puzzle_data, &SafetyPolicy::part_2());
+}
 
-        safe_reports += 1;
-    }
+fn fleet(puzzle_data: &[Vec<isize>], policy: &SafetyPolicy) {
+    println!("Day 02 - Fleet policy");
 
-    println!("Safe reports: {}", safe_reports);
-    println!("Total analyzed reports: {}", analyzed_reports);
+    count_safe_reports(puzzle_data, policy);
 }
 
-fn part2(puzzle_data: Vec<Vec<isize>>) {
-    println!("Day 02 - Part 2");
-
+fn count_safe_reports(puzzle_data: &[Vec<isize>], policy: &SafetyPolicy) {
     let mut safe_reports = 0;
     let mut analyzed_reports = 0;
     for report in puzzle_data {
         analyzed_reports += 1;
 
-        let mut levels = report.iter();
-        let mut previous_level = levels.next().unwrap();
-        let mut next_level = levels.next().unwrap();
-
-        let mut bad_levels = 0;
-        let mut ordering = next_level.cmp(previous_level);
-
-        while ordering == Equal {
-            bad_levels += 1;
-            previous_level = next_level;
-            let lev = levels.next();
-            if lev.is_none() {
-                break;
-            }
-
-            next_level = lev.unwrap();
-            ordering = next_level.cmp(previous_level);
+        if policy.is_safe(report) {
+            safe_reports += 1;
         }
+    }
 
-        if (next_level - previous_level).abs() > 3 {
-            bad_levels += 1;
-        }
+    println!("Safe reports: {}", safe_reports);
+    println!("Total analyzed reports: {}", analyzed_reports);
+}
 
-        previous_level = next_level;
-        for level in levels {
-            if level.cmp(previous_level) != ordering || (level - previous_level).abs() > 3 {
-                bad_levels += 1;
-            }
+fn repairs(puzzle_data: &[Vec<isize>]) {
+    println!("Day 02 - Repairs");
 
-            previous_level = level;
-        }
+    let policy = SafetyPolicy::part_1();
 
-        if bad_levels <= 1 {
-            safe_reports += 1;
+    let mut repaired_reports = 0;
+    let mut edited_levels = 0;
+    let mut total_adjustment = 0;
+    for report in puzzle_data {
+        if policy.is_safe(report) {
+            continue;
         }
+
+        let repair = minimal_repair(&policy, report);
+        repaired_reports += 1;
+        edited_levels += repair.edits.len();
+        total_adjustment += repair.total_adjustment;
     }
 
-    println!("Safe reports: {}", safe_reports);
-    println!("Total analyzed reports: {}", analyzed_reports);
+    println!("Repaired reports: {}", repaired_reports);
+    println!("Edited levels: {}", edited_levels);
+    println!("Total adjustment: {}", total_adjustment);
 }
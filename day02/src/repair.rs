@@ -0,0 +1,126 @@
+use std::cmp::Ordering;
+
+use crate::safety_policy::{AllowedDirection, SafetyPolicy};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelEdit {
+    pub index: usize,
+    pub from: isize,
+    pub to: isize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repair {
+    pub edits: Vec<LevelEdit>,
+    pub total_adjustment: isize,
+}
+
+// Finds the fewest levels to change so the report satisfies the policy,
+// breaking ties by the smallest total absolute adjustment. Dampener tolerance
+// is not used here, as values are corrected instead of levels being dropped.
+pub fn minimal_repair(policy: &SafetyPolicy, report: &[isize]) -> Repair {
+    let orderings = match policy.direction {
+        AllowedDirection::Increasing => vec![Ordering::Greater],
+        AllowedDirection::Decreasing => vec![Ordering::Less],
+        AllowedDirection::Either => vec![Ordering::Greater, Ordering::Less],
+    };
+
+    orderings
+        .into_iter()
+        .map(|ordering| minimal_repair_in_direction(policy, report, ordering))
+        .min_by_key(|repair| (repair.edits.len(), repair.total_adjustment))
+        .unwrap()
+}
+
+fn minimal_repair_in_direction(
+    policy: &SafetyPolicy,
+    report: &[isize],
+    ordering: Ordering,
+) -> Repair {
+    if report.is_empty() {
+        return Repair {
+            edits: Vec::new(),
+            total_adjustment: 0,
+        };
+    }
+
+    // At least one level is always kept, so every repaired value lies within
+    // (len - 1) maximum steps of some original level. Only those values are
+    // candidates, which bounds the table by the report's length rather than
+    // the range of its readings.
+    let reach = (report.len() as isize - 1) * policy.max_step;
+    let mut candidates: Vec<isize> = report
+        .iter()
+        .flat_map(|level| (level - reach)..=(level + reach))
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    let width = candidates.len();
+
+    let level_cost = |i: usize, value: isize| {
+        let adjustment = (value - report[i]).abs();
+        ((adjustment != 0) as usize, adjustment)
+    };
+
+    let mut costs: Vec<Vec<Option<(usize, isize)>>> = vec![vec![None; width]; report.len()];
+    let mut parents = vec![vec![0; width]; report.len()];
+
+    for (v, cost) in costs[0].iter_mut().enumerate() {
+        *cost = Some(level_cost(0, candidates[v]));
+    }
+
+    for i in 1..report.len() {
+        for prev_v in 0..width {
+            let Some(prev_cost) = costs[i - 1][prev_v] else {
+                continue;
+            };
+            let prev_value = candidates[prev_v];
+
+            for step in -policy.max_step..=policy.max_step {
+                let value = prev_value + step;
+                if !policy.is_valid_step(prev_value, value, ordering) {
+                    continue;
+                }
+                let Ok(v) = candidates.binary_search(&value) else {
+                    continue;
+                };
+
+                let (changed, adjustment) = level_cost(i, value);
+                let candidate = (prev_cost.0 + changed, prev_cost.1 + adjustment);
+
+                if costs[i][v].is_none_or(|current| candidate < current) {
+                    costs[i][v] = Some(candidate);
+                    parents[i][v] = prev_v;
+                }
+            }
+        }
+    }
+
+    let last = report.len() - 1;
+    let (mut v, (_, total_adjustment)) = costs[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(v, cost)| cost.map(|c| (v, c)))
+        .min_by_key(|&(_, cost)| cost)
+        .expect("a report can always be repaired by rewriting its levels");
+
+    let mut edits = Vec::new();
+    for i in (0..report.len()).rev() {
+        let value = candidates[v];
+        if value != report[i] {
+            edits.push(LevelEdit {
+                index: i,
+                from: report[i],
+                to: value,
+            });
+        }
+
+        v = parents[i][v];
+    }
+    edits.reverse();
+
+    Repair {
+        edits,
+        total_adjustment,
+    }
+}
@@ -0,0 +1,101 @@
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AllowedDirection {
+    Increasing,
+    Decreasing,
+    Either,
+}
+
+impl From<&str> for AllowedDirection {
+    fn from(s: &str) -> Self {
+        match s {
+            "increasing" => Self::Increasing,
+            "decreasing" => Self::Decreasing,
+            "either" => Self::Either,
+            _ => panic!("Unknown direction {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SafetyPolicy {
+    pub min_step: isize,
+    pub max_step: isize,
+    pub direction: AllowedDirection,
+    pub allow_plateaus: bool,
+    // How many levels the dampener may drop from a report
+    pub tolerance: usize,
+}
+
+impl SafetyPolicy {
+    pub fn part_1() -> Self {
+        Self {
+            min_step: 1,
+            max_step: 3,
+            direction: AllowedDirection::Either,
+            allow_plateaus: false,
+            tolerance: 0,
+        }
+    }
+
+    pub fn part_2() -> Self {
+        Self {
+            tolerance: 1,
+            ..Self::part_1()
+        }
+    }
+
+    pub fn is_safe(&self, report: &[isize]) -> bool {
+        self.removals_needed(report) <= self.tolerance
+    }
+
+    pub fn removals_needed(&self, report: &[isize]) -> usize {
+        match self.direction {
+            AllowedDirection::Either => self
+                .removals_needed_in_direction(report, Ordering::Greater)
+                .min(self.removals_needed_in_direction(report, Ordering::Less)),
+            AllowedDirection::Increasing => {
+                self.removals_needed_in_direction(report, Ordering::Greater)
+            }
+            AllowedDirection::Decreasing => {
+                self.removals_needed_in_direction(report, Ordering::Less)
+            }
+        }
+    }
+
+    pub fn is_valid_step(&self, from: isize, to: isize, ordering: Ordering) -> bool {
+        match to.cmp(&from) {
+            Ordering::Equal => self.allow_plateaus,
+            o if o != ordering => false,
+            _ => (self.min_step..=self.max_step).contains(&(to - from).abs()),
+        }
+    }
+
+    // Longest chain of levels that can be kept, where removals_before[i] is the
+    // fewest levels that must be dropped before i when level i is kept.
+    fn removals_needed_in_direction(&self, report: &[isize], ordering: Ordering) -> usize {
+        if report.is_empty() {
+            return 0;
+        }
+
+        let mut removals_before = Vec::with_capacity(report.len());
+        for i in 0..report.len() {
+            let mut best = i;
+            for j in 0..i {
+                if self.is_valid_step(report[j], report[i], ordering) {
+                    best = best.min(removals_before[j] + i - j - 1);
+                }
+            }
+
+            removals_before.push(best);
+        }
+
+        removals_before
+            .iter()
+            .enumerate()
+            .map(|(i, removals)| removals + report.len() - 1 - i)
+            .min()
+            .unwrap()
+    }
+}
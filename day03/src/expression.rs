@@ -0,0 +1,275 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+    ops::Range,
+};
+
+use crate::{
+    instructions::{InstructionSpec, Matcher},
+    interpreter::{Instruction, Statement},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Mul,
+    Add,
+    Sub,
+    Div,
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Mul => "mul",
+            Operation::Add => "add",
+            Operation::Sub => "sub",
+            Operation::Div => "div",
+        }
+    }
+
+    fn all() -> [Operation; 4] {
+        [
+            Operation::Mul,
+            Operation::Add,
+            Operation::Sub,
+            Operation::Div,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    DivisionByZero,
+    Overflow,
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::DivisionByZero => write!(f, "division by zero"),
+            EvalError::Overflow => write!(f, "overflow"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Integer(isize),
+    Call(Operation, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn evaluate(&self) -> Result<isize, EvalError> {
+        match self {
+            Expr::Integer(n) => Ok(*n),
+            Expr::Call(operation, a, b) => {
+                let (a, b) = (a.evaluate()?, b.evaluate()?);
+
+                match operation {
+                    Operation::Mul => a.checked_mul(b).ok_or(EvalError::Overflow),
+                    Operation::Add => a.checked_add(b).ok_or(EvalError::Overflow),
+                    Operation::Sub => a.checked_sub(b).ok_or(EvalError::Overflow),
+                    Operation::Div if b == 0 => Err(EvalError::DivisionByZero),
+                    Operation::Div => a.checked_div(b).ok_or(EvalError::Overflow),
+                }
+            }
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Integer(n) => write!(f, "{}", n),
+            Expr::Call(operation, a, b) => write!(f, "{}({},{})", operation.name(), a, b),
+        }
+    }
+}
+
+// Maximum call nesting, deeper expressions are rejected rather than parsed,
+// which also bounds the recursion in evaluating and printing them
+const MAX_DEPTH: usize = 128;
+
+struct Frame {
+    operation: Operation,
+    start: usize,
+    first: Option<(Expr, Range<usize>)>,
+}
+
+// Parser for the extended language, where call arguments may be integers or
+// further calls, e.g. `mul(add(1,2),3)`. Plain instructions come from the same
+// instruction table the lexer uses, nested calls are parsed with an explicit
+// stack of open calls.
+pub struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+    matchers: Vec<Matcher>,
+    pending: VecDeque<Statement>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a [u8], instruction_set: &[InstructionSpec]) -> Self {
+        Self {
+            input,
+            position: 0,
+            matchers: instruction_set.iter().map(Matcher::new).collect(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    fn statement(&mut self) {
+        let start = self.position;
+
+        for matcher in self.matchers.iter_mut() {
+            let Some(token) = matcher.match_at(self.input, start) else {
+                continue;
+            };
+
+            if let Some(statement) = Statement::from_token(&token) {
+                self.position = token.span.end;
+                self.pending.push_back(statement);
+                return;
+            }
+        }
+
+        match self.call() {
+            Ok(expr) => self.pending.push_back(Statement {
+                instruction: Instruction::from_expr(expr),
+                span: start..self.position,
+            }),
+            Err(completed) => {
+                // Any call starting inside the failed prefix either completed,
+                // and is returned here, or was still open and would fail at the
+                // same byte, so parsing resumes from there
+                self.pending.extend(completed);
+                if self.position == start {
+                    self.position += 1;
+                }
+            }
+        }
+    }
+
+    // On failure, returns the outermost calls that did complete before it
+    fn call(&mut self) -> Result<Expr, Vec<Statement>> {
+        let mut frames: Vec<Frame> = Vec::new();
+
+        loop {
+            let start = self.position;
+            let is_digit = self.input.get(start).is_some_and(u8::is_ascii_digit);
+
+            let mut value = if is_digit && !frames.is_empty() {
+                match self.integer() {
+                    Some(n) => (Expr::Integer(n), start..self.position),
+                    None => return Err(completed_calls(frames, None)),
+                }
+            } else {
+                if frames.len() == MAX_DEPTH {
+                    return Err(completed_calls(frames, None));
+                }
+
+                match self.open_call() {
+                    Some(operation) => {
+                        frames.push(Frame {
+                            operation,
+                            start,
+                            first: None,
+                        });
+                        continue;
+                    }
+                    None => return Err(completed_calls(frames, None)),
+                }
+            };
+
+            loop {
+                let frame = frames.last_mut().unwrap();
+                if frame.first.is_none() {
+                    frame.first = Some(value);
+                    if !self.literal(b",") {
+                        return Err(completed_calls(frames, None));
+                    }
+                    break;
+                }
+
+                if !self.literal(b")") {
+                    return Err(completed_calls(frames, Some(value)));
+                }
+
+                let frame = frames.pop().unwrap();
+                let (first, _) = frame.first.unwrap();
+                value = (
+                    Expr::Call(frame.operation, Box::new(first), Box::new(value.0)),
+                    frame.start..self.position,
+                );
+
+                if frames.is_empty() {
+                    return Ok(value.0);
+                }
+            }
+        }
+    }
+
+    fn open_call(&mut self) -> Option<Operation> {
+        let operation = Operation::all()
+            .into_iter()
+            .find(|op| self.input[self.position..].starts_with(op.name().as_bytes()))?;
+
+        let start = self.position;
+        self.position += operation.name().len();
+        if !self.literal(b"(") {
+            self.position = start;
+            return None;
+        }
+
+        Some(operation)
+    }
+
+    fn integer(&mut self) -> Option<isize> {
+        let mut value: isize = 0;
+        while let Some(b) = self.input.get(self.position).filter(|b| b.is_ascii_digit()) {
+            value = value.checked_mul(10)?.checked_add((b - b'0') as isize)?;
+            self.position += 1;
+        }
+
+        Some(value)
+    }
+
+    fn literal(&mut self, expected: &[u8]) -> bool {
+        if !self.input[self.position..].starts_with(expected) {
+            return false;
+        }
+
+        self.position += expected.len();
+        true
+    }
+}
+
+impl Iterator for Parser<'_> {
+    type Item = Statement;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            if self.position >= self.input.len() {
+                return None;
+            }
+
+            self.statement();
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+// Completed operands of the calls still open, plus the one just finished if
+// any, in input order. Nested calls inside them are part of their statement.
+fn completed_calls(frames: Vec<Frame>, last: Option<(Expr, Range<usize>)>) -> Vec<Statement> {
+    frames
+        .into_iter()
+        .filter_map(|frame| frame.first)
+        .chain(last)
+        .filter(|(expr, _)| matches!(expr, Expr::Call(..)))
+        .map(|(expr, span)| Statement {
+            instruction: Instruction::from_expr(expr),
+            span,
+        })
+        .collect()
+}
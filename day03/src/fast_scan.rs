@@ -0,0 +1,48 @@
+use memchr::{memchr2_iter, memchr3_iter, memchr_iter};
+
+use crate::instructions::{InstructionSpec, Matcher, Token};
+
+// Jumps between bytes that can start an instruction and only runs the full
+// matchers there. As long as each name's first byte appears nowhere else in
+// its pattern, as with mul/do/don't, no match can begin inside another one and
+// the tokens are the same the byte-at-a-time lexer produces.
+pub fn scan(input: &[u8], instruction_set: &[InstructionSpec]) -> Vec<Token> {
+    let mut matchers: Vec<(u8, Matcher)> = instruction_set
+        .iter()
+        .map(|spec| (spec.name.as_bytes()[0], Matcher::new(spec)))
+        .collect();
+
+    let mut first_bytes: Vec<u8> = matchers.iter().map(|(b, _)| *b).collect();
+    first_bytes.sort();
+    first_bytes.dedup();
+
+    let candidates: Box<dyn Iterator<Item = usize>> = match first_bytes.as_slice() {
+        [a] => Box::new(memchr_iter(*a, input)),
+        [a, b] => Box::new(memchr2_iter(*a, *b, input)),
+        [a, b, c] => Box::new(memchr3_iter(*a, *b, *c, input)),
+        _ => Box::new(
+            input
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| first_bytes.contains(b))
+                .map(|(i, _)| i),
+        ),
+    };
+
+    let mut tokens = Vec::new();
+    for start in candidates {
+        for (first_byte, matcher) in matchers.iter_mut() {
+            if *first_byte != input[start] {
+                continue;
+            }
+
+            if let Some(token) = matcher.match_at(input, start) {
+                tokens.push(token);
+            }
+        }
+    }
+
+    // The lexer emits tokens as they complete
+    tokens.sort_by_key(|token| token.span.end);
+    tokens
+}
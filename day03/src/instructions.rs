@@ -1,291 +1,247 @@
-#[derive(Debug, PartialEq)]
-enum MulInstructionState {
-    M,
-    U,
-    L,
-    OpenParenthesis,
-    FirstNumber(String),
-    Comma,
-    SecondNumber(String),
-    CloseParenthesis,
-    Other,
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgPattern {
+    Integer { max_digits: Option<usize> },
 }
 
-#[derive(Debug, PartialEq)]
-enum DoInstructionState {
-    D,
-    O,
-    OpenParenthesis,
-    CloseParenthesis,
-    Other,
+#[derive(Debug, Clone)]
+pub struct InstructionSpec {
+    pub name: &'static str,
+    pub args: Vec<ArgPattern>,
 }
 
-#[derive(Debug, PartialEq)]
-enum DontInstructionState {
-    D,
-    O,
-    N,
-    Apostrophe,
-    T,
-    OpenParenthesis,
-    CloseParenthesis,
-    Other,
+impl InstructionSpec {
+    pub fn new(name: &'static str, args: Vec<ArgPattern>) -> Self {
+        Self { name, args }
+    }
 }
 
-pub struct Mul {
-    instruction: String,
-    num1: Option<isize>,
-    num2: Option<isize>,
-    state: MulInstructionState,
+pub fn puzzle_instruction_set() -> Vec<InstructionSpec> {
+    instruction_set_with_max_digits(None)
 }
 
-impl Mul {
-    pub fn new() -> Mul {
-        Mul {
-            instruction: String::new(),
-            num1: None,
-            num2: None,
-            state: MulInstructionState::Other,
-        }
-    }
+// Enforces the 1-3 digit operand limit stated by the puzzle
+pub fn strict_puzzle_instruction_set() -> Vec<InstructionSpec> {
+    instruction_set_with_max_digits(Some(3))
+}
 
-    pub fn add_char(&mut self, c: char) {
-        match c {
-            'm' => {
-                self.clear();
-                self.state = MulInstructionState::M;
-            }
-            'u' => {
-                if self.state != MulInstructionState::M {
-                    self.clear();
-                    return;
-                }
+fn instruction_set_with_max_digits(max_digits: Option<usize>) -> Vec<InstructionSpec> {
+    let integer = ArgPattern::Integer { max_digits };
 
-                self.state = MulInstructionState::U;
-            }
-            'l' => {
-                if self.state != MulInstructionState::U {
-                    self.clear();
-                    return;
-                }
+    vec![
+        InstructionSpec::new("mul", vec![integer.clone(), integer]),
+        InstructionSpec::new("do", Vec::new()),
+        InstructionSpec::new("don't", Vec::new()),
+    ]
+}
 
-                self.state = MulInstructionState::L;
-            }
-            '(' => {
-                if self.state != MulInstructionState::L {
-                    self.clear();
-                    return;
-                }
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub name: &'static str,
+    pub args: Vec<isize>,
+    pub span: Range<usize>,
+}
 
-                self.state = MulInstructionState::OpenParenthesis;
-            }
-            ',' => {
-                if let MulInstructionState::FirstNumber(num) = &self.state {
-                    self.num1 = Some(num.parse::<isize>().unwrap());
-                } else {
-                    self.clear();
-                    return;
-                }
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearMiss {
+    pub name: &'static str,
+    pub start: usize,
+    pub failing_char: char,
+    pub position: usize,
+}
 
-                self.state = MulInstructionState::Comma;
-            }
-            ')' => {
-                if let MulInstructionState::SecondNumber(num) = &self.state {
-                    self.num2 = Some(num.parse::<isize>().unwrap());
-                } else {
-                    self.clear();
-                    return;
-                }
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Literal(u8),
+    Arg(ArgPattern),
+}
 
-                self.state = MulInstructionState::CloseParenthesis;
-            }
-            '0'..='9' => match &self.state {
-                MulInstructionState::OpenParenthesis => {
-                    self.state = MulInstructionState::FirstNumber(c.to_string());
-                }
-                MulInstructionState::FirstNumber(num) => {
-                    self.state = MulInstructionState::FirstNumber(format!("{}{}", num, c));
-                }
-                MulInstructionState::SecondNumber(num) => {
-                    self.state = MulInstructionState::SecondNumber(format!("{}{}", num, c));
-                }
-                MulInstructionState::Comma => {
-                    self.state = MulInstructionState::SecondNumber(c.to_string());
-                }
-                _ => {
-                    self.clear();
-                    return;
-                }
-            },
-            _ => {
-                self.clear();
-                return;
+pub struct Matcher {
+    name: &'static str,
+    elements: Vec<Element>,
+    position: usize,
+    start: usize,
+    digits: usize,
+    current_arg: isize,
+    args: Vec<isize>,
+}
+
+impl Matcher {
+    pub fn new(spec: &InstructionSpec) -> Self {
+        let mut elements: Vec<Element> = spec.name.bytes().map(Element::Literal).collect();
+        elements.push(Element::Literal(b'('));
+        for (i, arg) in spec.args.iter().enumerate() {
+            if i > 0 {
+                elements.push(Element::Literal(b','));
             }
+            elements.push(Element::Arg(arg.clone()));
+        }
+        elements.push(Element::Literal(b')'));
+
+        Self {
+            name: spec.name,
+            elements,
+            position: 0,
+            start: 0,
+            digits: 0,
+            current_arg: 0,
+            args: Vec::new(),
         }
-
-        self.instruction.push(c);
     }
 
-    pub fn clear(&mut self) {
-        self.instruction.clear();
-        self.num1 = None;
-        self.num2 = None;
-        self.state = MulInstructionState::Other;
-    }
+    fn feed(
+        &mut self,
+        byte: u8,
+        offset: usize,
+        near_misses: Option<&mut Vec<NearMiss>>,
+    ) -> Option<Token> {
+        if !self.advance(byte) {
+            // Only report prefixes that made it past the opening parenthesis
+            if let Some(near_misses) = near_misses {
+                if self.position > self.name.len() {
+                    near_misses.push(NearMiss {
+                        name: self.name,
+                        start: self.start,
+                        failing_char: byte as char,
+                        position: offset,
+                    });
+                }
+            }
+
+            self.clear();
+            // A failing byte may still start a new instruction
+            self.advance(byte);
+        }
 
-    pub fn is_done(&self) -> bool {
-        self.state == MulInstructionState::CloseParenthesis
-    }
+        if self.position == 1 {
+            self.start = offset;
+        }
+
+        if self.position == self.elements.len() {
+            let token = Token {
+                name: self.name,
+                args: std::mem::take(&mut self.args),
+                span: self.start..offset + 1,
+            };
+            self.clear();
+            return Some(token);
+        }
 
-    pub fn evaluate(&self) -> Option<isize> {
-        Some(self.num1? * self.num2?)
+        None
     }
-}
 
-pub struct Do {
-    instruction: String,
-    state: DoInstructionState,
-}
+    // Runs the matcher from a known instruction start instead of byte by byte
+    // over the whole input
+    pub fn match_at(&mut self, input: &[u8], start: usize) -> Option<Token> {
+        self.clear();
+        self.start = start;
 
-impl Do {
-    pub fn new() -> Do {
-        Do {
-            instruction: String::new(),
-            state: DoInstructionState::D,
-        }
-    }
+        for (offset, &byte) in input.iter().enumerate().skip(start) {
+            if !self.advance(byte) {
+                self.clear();
+                return None;
+            }
 
-    pub fn add_char(&mut self, c: char) {
-        match c {
-            'd' => {
+            if self.position == self.elements.len() {
+                let token = Token {
+                    name: self.name,
+                    args: std::mem::take(&mut self.args),
+                    span: start..offset + 1,
+                };
                 self.clear();
-                self.state = DoInstructionState::D;
+                return Some(token);
             }
-            'o' => {
-                if self.state != DoInstructionState::D {
-                    self.clear();
-                    return;
+        }
+
+        self.clear();
+        None
+    }
+
+    fn advance(&mut self, byte: u8) -> bool {
+        match &self.elements[self.position] {
+            Element::Literal(expected) => {
+                if byte != *expected {
+                    return false;
                 }
 
-                self.state = DoInstructionState::O;
+                self.position += 1;
+                true
             }
-            '(' => {
-                if self.state != DoInstructionState::O {
-                    self.clear();
-                    return;
+            Element::Arg(ArgPattern::Integer { max_digits }) => {
+                if byte.is_ascii_digit() && max_digits.is_none_or(|max| self.digits < max) {
+                    let Some(value) = self
+                        .current_arg
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add((byte - b'0') as isize))
+                    else {
+                        return false;
+                    };
+
+                    self.current_arg = value;
+                    self.digits += 1;
+                    return true;
                 }
 
-                self.state = DoInstructionState::OpenParenthesis;
-            }
-            ')' => {
-                if self.state != DoInstructionState::OpenParenthesis {
-                    self.clear();
-                    return;
+                if self.digits == 0 {
+                    return false;
                 }
 
-                self.state = DoInstructionState::CloseParenthesis;
-            }
-            _ => {
-                self.clear();
-                return;
+                self.args.push(self.current_arg);
+                self.current_arg = 0;
+                self.digits = 0;
+                self.position += 1;
+                self.advance(byte)
             }
         }
-
-        self.instruction.push(c);
     }
 
-    pub fn clear(&mut self) {
-        self.instruction.clear();
-        self.state = DoInstructionState::Other;
-    }
-
-    pub fn is_done(&self) -> bool {
-        self.state == DoInstructionState::CloseParenthesis
+    fn clear(&mut self) {
+        self.position = 0;
+        self.digits = 0;
+        self.current_arg = 0;
+        self.args.clear();
     }
 }
 
-pub struct Dont {
-    instruction: String,
-    state: DontInstructionState,
+pub struct Lexer {
+    matchers: Vec<Matcher>,
+    offset: usize,
+    near_misses: Option<Vec<NearMiss>>,
 }
 
-impl Dont {
-    pub fn new() -> Dont {
-        Dont {
-            instruction: String::new(),
-            state: DontInstructionState::D,
+impl Lexer {
+    pub fn new(instruction_set: &[InstructionSpec]) -> Self {
+        Self {
+            matchers: instruction_set.iter().map(Matcher::new).collect(),
+            offset: 0,
+            near_misses: None,
         }
     }
 
-    pub fn add_char(&mut self, c: char) {
-        match c {
-            'd' => {
-                self.clear();
-                self.state = DontInstructionState::D;
-            }
-            'o' => {
-                if self.state != DontInstructionState::D {
-                    self.clear();
-                    return;
-                }
-
-                self.state = DontInstructionState::O;
-            }
-            'n' => {
-                if self.state != DontInstructionState::O {
-                    self.clear();
-                    return;
-                }
-
-                self.state = DontInstructionState::N;
-            }
-            '\'' => {
-                if self.state != DontInstructionState::N {
-                    self.clear();
-                    return;
-                }
-
-                self.state = DontInstructionState::Apostrophe;
-            }
-            't' => {
-                if self.state != DontInstructionState::Apostrophe {
-                    self.clear();
-                    return;
-                }
-
-                self.state = DontInstructionState::T;
-            }
-            '(' => {
-                if self.state != DontInstructionState::T {
-                    self.clear();
-                    return;
-                }
+    pub fn with_diagnostics(mut self) -> Self {
+        self.near_misses = Some(Vec::new());
+        self
+    }
 
-                self.state = DontInstructionState::OpenParenthesis;
-            }
-            ')' => {
-                if self.state != DontInstructionState::OpenParenthesis {
-                    self.clear();
-                    return;
-                }
+    pub fn feed(&mut self, byte: u8) -> Vec<Token> {
+        let offset = self.offset;
+        self.offset += 1;
 
-                self.state = DontInstructionState::CloseParenthesis;
-            }
-            _ => {
-                self.clear();
-                return;
+        let mut tokens = Vec::new();
+        for matcher in self.matchers.iter_mut() {
+            if let Some(token) = matcher.feed(byte, offset, self.near_misses.as_mut()) {
+                tokens.push(token);
             }
         }
 
-        self.instruction.push(c);
-    }
-
-    pub fn clear(&mut self) {
-        self.instruction.clear();
-        self.state = DontInstructionState::Other;
+        tokens
     }
 
-    pub fn is_done(&self) -> bool {
-        self.state == DontInstructionState::CloseParenthesis
+    pub fn take_near_misses(&mut self) -> Vec<NearMiss> {
+        self.near_misses
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
     }
 }
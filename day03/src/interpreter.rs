@@ -0,0 +1,148 @@
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+};
+
+use crate::{
+    expression::{EvalError, Expr, Operation},
+    instructions::Token,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    Mul(isize, isize),
+    Do,
+    Dont,
+    Expression(Expr),
+}
+
+impl Instruction {
+    pub fn from_token(token: &Token) -> Option<Self> {
+        match (token.name, token.args.as_slice()) {
+            ("mul", &[a, b]) => Some(Self::Mul(a, b)),
+            ("do", []) => Some(Self::Do),
+            ("don't", []) => Some(Self::Dont),
+            _ => None,
+        }
+    }
+
+    // A plain `mul` of two integers is the same instruction however it was
+    // read, so it only has one representation
+    pub fn from_expr(expr: Expr) -> Self {
+        match expr {
+            Expr::Call(Operation::Mul, a, b) => match (*a, *b) {
+                (Expr::Integer(a), Expr::Integer(b)) => Self::Mul(a, b),
+                (a, b) => Self::Expression(Expr::Call(Operation::Mul, Box::new(a), Box::new(b))),
+            },
+            expr => Self::Expression(expr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub instruction: Instruction,
+    pub span: Range<usize>,
+}
+
+impl Statement {
+    pub fn from_token(token: &Token) -> Option<Self> {
+        Some(Self {
+            instruction: Instruction::from_token(token)?,
+            span: token.span.clone(),
+        })
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Mul(a, b) => write!(f, "mul({},{})", a, b),
+            Instruction::Do => write!(f, "do()"),
+            Instruction::Dont => write!(f, "don't()"),
+            Instruction::Expression(expr) => write!(f, "{}", expr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub instruction: Instruction,
+    pub span: Range<usize>,
+    pub executed: bool,
+    pub contribution: isize,
+    pub error: Option<EvalError>,
+}
+
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}..{} {}",
+            self.span.start, self.span.end, self.instruction
+        )?;
+
+        if let Some(error) = &self.error {
+            write!(f, " failed: {}", error)
+        } else if self.executed {
+            write!(f, " executed {:+}", self.contribution)
+        } else {
+            write!(f, " skipped")
+        }
+    }
+}
+
+pub struct Interpreter {
+    pub enabled: bool,
+    pub accumulator: isize,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            accumulator: 0,
+        }
+    }
+
+    pub fn step(&mut self, statement: &Statement) -> TraceEntry {
+        let mut entry = TraceEntry {
+            instruction: statement.instruction.clone(),
+            span: statement.span.clone(),
+            executed: true,
+            contribution: 0,
+            error: None,
+        };
+
+        match &statement.instruction {
+            Instruction::Do => self.enabled = true,
+            Instruction::Dont => self.enabled = false,
+            _ if !self.enabled => entry.executed = false,
+            instruction => match self.execute(instruction) {
+                Ok(value) => entry.contribution = value,
+                Err(error) => {
+                    entry.executed = false;
+                    entry.error = Some(error);
+                }
+            },
+        }
+
+        entry
+    }
+
+    // Adds the instruction's value to the accumulator, leaving it untouched
+    // if either overflows
+    fn execute(&mut self, instruction: &Instruction) -> Result<isize, EvalError> {
+        let value = match instruction {
+            Instruction::Mul(a, b) => a.checked_mul(*b).ok_or(EvalError::Overflow)?,
+            Instruction::Expression(expr) => expr.evaluate()?,
+            Instruction::Do | Instruction::Dont => 0,
+        };
+
+        self.accumulator = self
+            .accumulator
+            .checked_add(value)
+            .ok_or(EvalError::Overflow)?;
+        Ok(value)
+    }
+}
@@ -1,69 +1,143 @@
+mod expression;
+mod fast_scan;
 mod instructions;
-
-use instructions::{Do, Dont, Mul};
-use std::fs;
+mod interpreter;
+mod scanner;
+
+use expression::Parser;
+use instructions::{puzzle_instruction_set, strict_puzzle_instruction_set, Lexer};
+use interpreter::{Instruction, Interpreter, Statement};
+use scanner::Scanner;
+use std::{
+    env,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    time::Instant,
+};
+
+const INPUT_PATH: &str = "inputs/day03.txt";
+const BENCHMARK_SIZE: usize = 64 * 1024 * 1024;
 
 fn main() {
-    let input = fs::read_to_string("inputs/day03.txt").unwrap();
+    if env::args().any(|arg| arg == "bench") {
+        benchmark();
+        return;
+    }
 
-    part1(&input);
-    part2(&input);
-}
+    part1();
+    part2();
+    diagnostics();
 
-fn part1(input: &str) {
-    println!("Day 03 - Part 1");
+    if env::args().any(|arg| arg == "--extended") {
+        extended();
+    }
+}
 
-    let mut sum = 0;
+fn statements() -> impl Iterator<Item = Statement> {
+    let file = File::open(INPUT_PATH).unwrap();
 
-    let mut instruction = Mul::new();
-    for c in input.chars() {
-        instruction.add_char(c);
+    Scanner::new(file, Lexer::new(&puzzle_instruction_set()))
+        .filter_map(|token| Statement::from_token(&token.unwrap()))
+}
 
-        if instruction.is_done() {
-            sum += instruction
-                .evaluate()
-                .expect("instruction should have two numbers");
+fn part1() {
+    println!("Day 03 - Part 1");
 
-            instruction.clear();
+    let mut interpreter = Interpreter::new();
+    for statement in statements() {
+        if matches!(statement.instruction, Instruction::Mul(..)) {
+            interpreter.step(&statement);
         }
     }
 
-    println!("Sum: {}", sum);
+    println!("Sum: {}", interpreter.accumulator);
 }
 
-fn part2(input: &str) {
+fn part2() {
     println!("Day 03 - Part 2");
 
-    let mut sum = 0;
-    let mut should_operate = true;
-    let mut mul_instr = Mul::new();
-    let mut do_instr = Do::new();
-    let mut dont_instr = Dont::new();
-    for c in input.chars() {
-        mul_instr.add_char(c);
-        do_instr.add_char(c);
-        dont_instr.add_char(c);
-
-        if mul_instr.is_done() {
-            if should_operate {
-                sum += mul_instr
-                    .evaluate()
-                    .expect("instruction should have two numbers");
-            }
-
-            mul_instr.clear();
-        }
+    let mut trace_file = BufWriter::new(File::create("day03/trace.txt").unwrap());
+
+    let mut interpreter = Interpreter::new();
+    for statement in statements() {
+        writeln!(trace_file, "{}", interpreter.step(&statement)).unwrap();
+    }
+    trace_file.flush().unwrap();
+
+    println!("Sum: {}", interpreter.accumulator);
+}
 
-        if do_instr.is_done() {
-            should_operate = true;
-            do_instr.clear();
+fn diagnostics() {
+    println!("Day 03 - Diagnostics");
+
+    let file = File::open(INPUT_PATH).unwrap();
+    let lexer = Lexer::new(&strict_puzzle_instruction_set()).with_diagnostics();
+    let mut scanner = Scanner::new(file, lexer);
+
+    let mut diagnostics_file = BufWriter::new(File::create("day03/diagnostics.txt").unwrap());
+
+    let mut strict_instructions = 0;
+    let mut near_misses = 0;
+    while let Some(tokens) = scanner.next_chunk() {
+        strict_instructions += tokens.unwrap().len();
+
+        // Near misses are drained after every chunk to keep memory bounded
+        for near_miss in scanner.lexer_mut().take_near_misses() {
+            near_misses += 1;
+            writeln!(
+                diagnostics_file,
+                "{}..{} {} failed on {:?}",
+                near_miss.start, near_miss.position, near_miss.name, near_miss.failing_char
+            )
+            .unwrap();
         }
+    }
+    diagnostics_file.flush().unwrap();
+
+    println!("Strict instructions: {}", strict_instructions);
+    println!("Near misses: {}", near_misses);
+}
 
-        if dont_instr.is_done() {
-            should_operate = false;
-            dont_instr.clear();
+fn extended() {
+    println!("Day 03 - Extended");
+
+    // Nested calls can span any distance, so unlike the other parts this reads
+    // the whole input at once
+    let input = fs::read(INPUT_PATH).unwrap();
+
+    let mut interpreter = Interpreter::new();
+    let mut failures = 0;
+    for statement in Parser::new(&input, &puzzle_instruction_set()) {
+        if interpreter.step(&statement).error.is_some() {
+            failures += 1;
         }
     }
 
-    println!("Sum: {}", sum);
+    println!("Sum: {}", interpreter.accumulator);
+    println!("Failed instructions: {}", failures);
+}
+
+fn benchmark() {
+    println!("Day 03 - Benchmark");
+
+    let input = fs::read(INPUT_PATH).unwrap();
+    let memory = input.repeat(BENCHMARK_SIZE / input.len() + 1);
+    let megabytes = memory.len() as f64 / (1024.0 * 1024.0);
+
+    let start = Instant::now();
+    let lexer = Lexer::new(&puzzle_instruction_set());
+    let lexed: Vec<_> = Scanner::new(memory.as_slice(), lexer)
+        .map(|token| token.unwrap())
+        .collect();
+    let lexer_time = start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    let scanned = fast_scan::scan(&memory, &puzzle_instruction_set());
+    let fast_scan_time = start.elapsed().as_secs_f64();
+
+    assert_eq!(lexed, scanned, "fast path should match the lexer");
+
+    println!("Scanned {:.0} MiB, {} tokens", megabytes, scanned.len());
+    println!("Lexer: {:.0} MiB/s", megabytes / lexer_time);
+    println!("Fast path: {:.0} MiB/s", megabytes / fast_scan_time);
 }
@@ -0,0 +1,68 @@
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind, Read},
+};
+
+use crate::instructions::{Lexer, Token};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Feeds the lexer one fixed-size chunk at a time. The lexer keeps its partial
+// matches between feeds, so instructions straddling two chunks are still found.
+pub struct Scanner<R: Read> {
+    reader: R,
+    lexer: Lexer,
+    chunk: Vec<u8>,
+    pending: VecDeque<Token>,
+}
+
+impl<R: Read> Scanner<R> {
+    pub fn new(reader: R, lexer: Lexer) -> Self {
+        Self {
+            reader,
+            lexer,
+            chunk: vec![0; CHUNK_SIZE],
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn lexer_mut(&mut self) -> &mut Lexer {
+        &mut self.lexer
+    }
+
+    // Lexes one more chunk of input, returning the tokens completed in it,
+    // possibly none. Callers that also drain the lexer's diagnostics after
+    // every chunk hold at most one chunk's worth of them.
+    pub fn next_chunk(&mut self) -> Option<io::Result<Vec<Token>>> {
+        loop {
+            let read = match self.reader.read(&mut self.chunk) {
+                Ok(0) => return None,
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let mut tokens = Vec::new();
+            for &byte in &self.chunk[..read] {
+                tokens.extend(self.lexer.feed(byte));
+            }
+
+            return Some(Ok(tokens));
+        }
+    }
+}
+
+impl<R: Read> Iterator for Scanner<R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            match self.next_chunk()? {
+                Ok(tokens) => self.pending.extend(tokens),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        self.pending.pop_front().map(Ok)
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::grid::{Grid, ALL_DIRECTIONS, ORTHOGONAL_DIRECTIONS};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Neighbourhood {
+    Four,
+    Eight,
+}
+
+impl Neighbourhood {
+    fn directions(&self) -> &'static [(isize, isize)] {
+        match self {
+            Neighbourhood::Four => &ORTHOGONAL_DIRECTIONS,
+            Neighbourhood::Eight => &ALL_DIRECTIONS,
+        }
+    }
+}
+
+impl From<&str> for Neighbourhood {
+    fn from(s: &str) -> Self {
+        match s {
+            "4" => Self::Four,
+            "8" => Self::Eight,
+            _ => panic!("Unknown neighbourhood {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Trie {
+    children: HashMap<char, Trie>,
+    // Indexes of every word ending here, as the list may repeat a word
+    words: Vec<usize>,
+}
+
+impl Trie {
+    fn insert(&mut self, word: &str, index: usize) {
+        let mut node = self;
+        for c in word.chars() {
+            node = node.children.entry(c).or_default();
+        }
+
+        node.words.push(index);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathMatch {
+    pub word: String,
+    pub path: Vec<(usize, usize)>,
+}
+
+// Finds words spelled along paths of adjacent cells that never reuse a cell.
+// Each word is reported once, with the first path found for it.
+pub fn find_paths(grid: &Grid, words: &[&str], neighbourhood: Neighbourhood) -> Vec<PathMatch> {
+    let mut trie = Trie::default();
+    for (i, word) in words.iter().enumerate() {
+        trie.insert(word, i);
+    }
+
+    let mut found: Vec<Option<Vec<(usize, usize)>>> = vec![None; words.len()];
+    let mut path = Vec::new();
+    for start in grid.positions() {
+        search(grid, neighbourhood, &trie, start, &mut path, &mut found);
+    }
+
+    words
+        .iter()
+        .zip(found)
+        .filter_map(|(word, path)| {
+            Some(PathMatch {
+                word: word.to_string(),
+                path: path?,
+            })
+        })
+        .collect()
+}
+
+fn search(
+    grid: &Grid,
+    neighbourhood: Neighbourhood,
+    trie: &Trie,
+    position: (usize, usize),
+    path: &mut Vec<(usize, usize)>,
+    found: &mut [Option<Vec<(usize, usize)>>],
+) {
+    if path.contains(&position) {
+        return;
+    }
+
+    // Prefixes no word continues from are pruned here
+    let Some(node) = grid.get(position).and_then(|c| trie.children.get(&c)) else {
+        return;
+    };
+
+    path.push(position);
+
+    for &word in &node.words {
+        if found[word].is_none() {
+            found[word] = Some(path.clone());
+        }
+    }
+
+    for &direction in neighbourhood.directions() {
+        if let Some(next) = grid.step(position, direction) {
+            search(grid, neighbourhood, node, next, path, found);
+        }
+    }
+
+    path.pop();
+}
@@ -0,0 +1,125 @@
+pub const ALL_DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+pub const ORTHOGONAL_DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Topology {
+    // Rectangular, every row must have the same length
+    Bounded,
+    // Each row has its own length, steps into a shorter row can fall off it
+    Ragged,
+    // Rectangular, steps off one edge come back in on the opposite one
+    Toroidal,
+}
+
+impl From<&str> for Topology {
+    fn from(s: &str) -> Self {
+        match s {
+            "bounded" => Self::Bounded,
+            "ragged" => Self::Ragged,
+            "toroidal" => Self::Toroidal,
+            _ => panic!("Unknown topology {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Grid {
+    rows: Vec<Vec<char>>,
+    topology: Topology,
+}
+
+impl From<&str> for Grid {
+    fn from(s: &str) -> Self {
+        Self {
+            rows: s.lines().map(|line| line.chars().collect()).collect(),
+            topology: Topology::Ragged,
+        }
+    }
+}
+
+impl Grid {
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        if topology != Topology::Ragged
+            && self.rows.iter().any(|row| row.len() != self.rows[0].len())
+        {
+            panic!(
+                "{:?} topology requires all rows to have the same length",
+                topology
+            );
+        }
+
+        self.topology = topology;
+        self
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn row_len(&self, x: usize) -> usize {
+        self.rows[x].len()
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<char> {
+        self.rows.get(x)?.get(y).copied()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.height()).flat_map(move |x| (0..self.row_len(x)).map(move |y| (x, y)))
+    }
+
+    // Position `count` steps from `start`, which the caller knows to be on the
+    // grid, e.g. because the line through `start` was already walked that far
+    pub fn advance(
+        &self,
+        (x, y): (usize, usize),
+        (step_x, step_y): (isize, isize),
+        count: usize,
+    ) -> (usize, usize) {
+        let (x, y) = (
+            x as isize + step_x * count as isize,
+            y as isize + step_y * count as isize,
+        );
+
+        if self.topology == Topology::Toroidal {
+            let (height, width) = (self.height() as isize, self.row_len(0) as isize);
+
+            return (x.rem_euclid(height) as usize, y.rem_euclid(width) as usize);
+        }
+
+        (x as usize, y as usize)
+    }
+
+    pub fn step(
+        &self,
+        (x, y): (usize, usize),
+        (step_x, step_y): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        if self.topology == Topology::Toroidal {
+            let (height, width) = (self.height() as isize, self.row_len(x) as isize);
+
+            return Some((
+                (x as isize + step_x).rem_euclid(height) as usize,
+                (y as isize + step_y).rem_euclid(width) as usize,
+            ));
+        }
+
+        let next = (x.checked_add_signed(step_x)?, y.checked_add_signed(step_y)?);
+
+        self.get(next).map(|_| next)
+    }
+}
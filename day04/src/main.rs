@@ -1,4 +1,17 @@
-use std::fs;
+mod boggle;
+mod grid;
+mod multi_search;
+mod render;
+mod template;
+mod word_search;
+
+use boggle::{find_paths, Neighbourhood};
+use grid::{Grid, Topology};
+use multi_search::find_words;
+use render::{highlights, render_ansi, render_html};
+use std::{env, fs};
+use template::{find_template, Template};
+use word_search::{find_word, WordSearchOptions};
 
 fn main() {
     let input = fs::read_to_string("inputs/day04.txt")
@@ -6,90 +19,84 @@ fn main() {
         .trim()
         .to_owned();
 
-    let mut puzzle = Vec::new();
-    for line in input.lines() {
-        puzzle.push(line.chars().collect());
+    let mut topology = Topology::Ragged;
+    let mut neighbourhood = None;
+    let mut word_list_path = None;
+    let mut render = false;
+    for arg in env::args().skip(1) {
+        if arg == "--render" {
+            render = true;
+        } else if let Some(name) = arg.strip_prefix("--topology=") {
+            topology = Topology::from(name);
+        } else if let Some(name) = arg.strip_prefix("--paths=") {
+            neighbourhood = Some(Neighbourhood::from(name));
+        } else {
+            word_list_path = Some(arg);
+        }
     }
 
-    part1(&puzzle);
-    part2(&puzzle);
-}
+    let grid = Grid::from(input.as_str()).with_topology(topology);
 
-fn part1(input: &[Vec<char>]) {
-    println!("Day 04 - Part 1");
+    part1(&grid);
+    part2(&grid);
 
-    let mut xmas_count = 0;
-    for x in 0..input.len() {
-        for y in 0..input[x].len() {
-            for step_x in -1..=1 {
-                for step_y in -1..=1 {
-                    if input[x][y] == 'X' && has_xmas(input, x, y, step_x, step_y) {
-                        xmas_count += 1;
-                    }
-                }
-            }
+    if let Some(word_list_path) = word_list_path {
+        match neighbourhood {
+            Some(neighbourhood) => paths(&grid, &word_list_path, neighbourhood),
+            None => dictionary(&grid, &word_list_path, render),
         }
     }
+}
+
+fn part1(grid: &Grid) {
+    println!("Day 04 - Part 1");
+
+    let xmas_count = find_word(grid, "XMAS", &WordSearchOptions::default()).len();
 
     println!("{}", xmas_count);
 }
 
-fn has_xmas(puzzle: &[Vec<char>], x: usize, y: usize, step_x: isize, step_y: isize) -> bool {
-    if puzzle[x][y] == 'S' {
-        return true;
-    }
+fn part2(grid: &Grid) {
+    println!("Day 04 - Part 2");
 
-    let next_letter = match puzzle[x][y] {
-        'X' => 'M',
-        'M' => 'A',
-        'A' => 'S',
-        _ => 'X',
-    };
-
-    if x as isize + step_x < 0
-        || x as isize + step_x >= puzzle.len() as isize
-        || y as isize + step_y < 0
-        || y as isize + step_y >= puzzle[(x as isize + step_x) as usize].len() as isize
-        || puzzle[(x as isize + step_x) as usize][(y as isize + step_y) as usize] != next_letter
-    {
-        return false;
-    }
+    let xmas_count = find_template(grid, &Template::from("M.S/.A./M.S"), true).len();
 
-    has_xmas(
-        puzzle,
-        (x as isize + step_x) as usize,
-        (y as isize + step_y) as usize,
-        step_x,
-        step_y,
-    )
+    println!("{}", xmas_count);
 }
 
-fn part2(puzzle: &[Vec<char>]) {
-    println!("Day 04 - Part 2");
+fn dictionary(grid: &Grid, word_list_path: &str, render: bool) {
+    println!("Day 04 - Dictionary");
 
-    let mut xmas_count = 0;
-    for x in 0..puzzle.len() {
-        for y in 0..puzzle[x].len() {
-            if puzzle[x][y] == 'A' && has_cross_mas(puzzle, x, y) {
-                xmas_count += 1;
-            }
-        }
+    let word_list = fs::read_to_string(word_list_path).unwrap();
+    let words: Vec<&str> = word_list.split_whitespace().collect();
+
+    let matches = find_words(grid, &words);
+    for (word, matches) in words.iter().zip(&matches) {
+        let positions: Vec<String> = matches
+            .iter()
+            .map(|m| format!("{:?}{:?}", m.start, m.direction))
+            .collect();
+
+        println!("{}: {} {}", word, matches.len(), positions.join(" "));
     }
 
-    println!("{}", xmas_count);
+    if render {
+        let highlighted = highlights(grid, &words, &matches);
+        print!("{}", render_ansi(grid, &highlighted));
+        fs::write("day04/matches.html", render_html(grid, &highlighted)).unwrap();
+    }
 }
 
-fn has_cross_mas(puzzle: &[Vec<char>], x: usize, y: usize) -> bool {
-    if x as isize - 1 < 0
-        || x + 1 >= puzzle.len()
-        || y as isize - 1 < 0
-        || y + 1 >= puzzle[x + 1].len()
-    {
-        return false;
+fn paths(grid: &Grid, word_list_path: &str, neighbourhood: Neighbourhood) {
+    println!("Day 04 - Paths");
+
+    let word_list = fs::read_to_string(word_list_path).unwrap();
+    let words: Vec<&str> = word_list.split_whitespace().collect();
+
+    let found = find_paths(grid, &words, neighbourhood);
+    for path_match in &found {
+        println!("{}: {:?}", path_match.word, path_match.path);
     }
 
-    ((puzzle[x + 1][y + 1] == 'M' && puzzle[x - 1][y - 1] == 'S')
-        || (puzzle[x + 1][y + 1] == 'S' && puzzle[x - 1][y - 1] == 'M'))
-        && ((puzzle[x + 1][y - 1] == 'M' && puzzle[x - 1][y + 1] == 'S')
-            || (puzzle[x + 1][y - 1] == 'S' && puzzle[x - 1][y + 1] == 'M'))
+    println!("Found {} of {} words", found.len(), words.len());
 }
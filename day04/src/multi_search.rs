@@ -0,0 +1,163 @@
+use aho_corasick::AhoCorasick;
+
+use crate::{
+    grid::{Grid, Topology},
+    word_search::WordMatch,
+};
+
+// Each line is scanned once forwards, with the reversed words standing in for
+// the backwards reading
+const LINE_DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+pub fn find_words(grid: &Grid, words: &[&str]) -> Vec<Vec<WordMatch>> {
+    let mut patterns = Vec::new();
+    let mut pattern_words = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        patterns.push(word.to_string());
+        pattern_words.push((i, false));
+
+        patterns.push(word.chars().rev().collect());
+        pattern_words.push((i, true));
+    }
+
+    let automaton = AhoCorasick::new(&patterns).expect("word list should build an automaton");
+
+    let longest_word = words.iter().map(|w| w.chars().count()).max().unwrap_or(0);
+
+    let mut matches = vec![Vec::new(); words.len()];
+    for direction in LINE_DIRECTIONS {
+        let backwards = (-direction.0, -direction.1);
+
+        // Only one line's text is held at a time, cells are located from
+        // their offset along the line
+        for line in lines(grid, direction, longest_word) {
+            let text = line.text(grid);
+            let ascii = text.is_ascii();
+            let cell = |offset: usize| {
+                if ascii {
+                    offset
+                } else {
+                    text[..offset].chars().count()
+                }
+            };
+
+            for m in automaton.find_overlapping_iter(&text) {
+                let first = cell(m.start());
+                if first >= line.starts {
+                    continue;
+                }
+                let last = cell(m.end()) - 1;
+
+                let (word, reversed) = pattern_words[m.pattern().as_usize()];
+                matches[word].push(if reversed {
+                    WordMatch {
+                        start: grid.advance(line.start, direction, last),
+                        direction: backwards,
+                    }
+                } else {
+                    WordMatch {
+                        start: grid.advance(line.start, direction, first),
+                        direction,
+                    }
+                });
+            }
+        }
+    }
+
+    matches
+}
+
+struct Line {
+    start: (usize, usize),
+    direction: (isize, isize),
+    // Cells read from the start, wrapping around on a torus
+    len: usize,
+    // Only matches beginning in the first `starts` cells count, the rest of
+    // the line is a wrapped-around tail of a toroidal cycle
+    starts: usize,
+}
+
+impl Line {
+    fn text(&self, grid: &Grid) -> String {
+        (0..self.len)
+            .map(|i| {
+                grid.get(grid.advance(self.start, self.direction, i))
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+fn lines(
+    grid: &Grid,
+    direction: (isize, isize),
+    longest_word: usize,
+) -> Box<dyn Iterator<Item = Line> + '_> {
+    if grid.topology() == Topology::Toroidal {
+        return Box::new(cycles(grid, direction, longest_word));
+    }
+
+    let backwards = (-direction.0, -direction.1);
+
+    // Lines start at cells with no predecessor in this direction
+    Box::new(
+        grid.positions()
+            .filter(move |&start| grid.step(start, backwards).is_none())
+            .map(move |start| {
+                let mut len = 1;
+                let mut position = start;
+                while let Some(next) = grid.step(position, direction) {
+                    position = next;
+                    len += 1;
+                }
+
+                Line {
+                    start,
+                    direction,
+                    len,
+                    starts: len,
+                }
+            }),
+    )
+}
+
+// On a torus every line is a cycle. Each one is read once around, followed by
+// enough of its start again for words to wrap across the seam. Stepping by
+// (dx, dy) keeps x, y or, on diagonals, y ∓ x fixed modulo the gcd of the
+// sides, so one start per fixed value covers every cycle exactly once.
+fn cycles(
+    grid: &Grid,
+    direction: (isize, isize),
+    longest_word: usize,
+) -> impl Iterator<Item = Line> + '_ {
+    let height = grid.height();
+    let width = if height == 0 { 0 } else { grid.row_len(0) };
+
+    let (starts, len): (Vec<(usize, usize)>, usize) = match direction {
+        _ if height == 0 || width == 0 => (Vec::new(), 0),
+        (0, _) => ((0..height).map(|x| (x, 0)).collect(), width),
+        (_, 0) => ((0..width).map(|y| (0, y)).collect(), height),
+        _ => {
+            let divisor = gcd(height, width);
+            (
+                (0..divisor).map(|y| (0, y)).collect(),
+                height / divisor * width,
+            )
+        }
+    };
+
+    starts.into_iter().map(move |start| Line {
+        start,
+        direction,
+        len: len + longest_word.saturating_sub(1),
+        starts: len,
+    })
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::{grid::Grid, word_search::WordMatch};
+
+const ANSI_COLORS: [&str; 6] = ["31", "32", "33", "34", "35", "36"];
+const ANSI_DIM: &str = "2";
+const HTML_COLORS: [&str; 6] = [
+    "#e06c75", "#98c379", "#e5c07b", "#61afef", "#c678dd", "#56b6c2",
+];
+
+// Maps every matched cell to the index of the word covering it. When words
+// overlap, the later one in the list wins.
+pub fn highlights(
+    grid: &Grid,
+    words: &[&str],
+    matches: &[Vec<WordMatch>],
+) -> HashMap<(usize, usize), usize> {
+    let mut highlighted = HashMap::new();
+    for (i, (word, word_matches)) in words.iter().zip(matches).enumerate() {
+        for m in word_matches {
+            for cell in m.cells(grid, word.chars().count()) {
+                highlighted.insert(cell, i);
+            }
+        }
+    }
+
+    highlighted
+}
+
+pub fn render_ansi(grid: &Grid, highlighted: &HashMap<(usize, usize), usize>) -> String {
+    let mut output = String::new();
+    for x in 0..grid.height() {
+        for y in 0..grid.row_len(x) {
+            let letter = grid.get((x, y)).unwrap();
+            let style = match highlighted.get(&(x, y)) {
+                Some(word) => ANSI_COLORS[word % ANSI_COLORS.len()],
+                None => ANSI_DIM,
+            };
+
+            output.push_str(&format!("\x1b[{}m{}\x1b[0m", style, letter));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+pub fn render_html(grid: &Grid, highlighted: &HashMap<(usize, usize), usize>) -> String {
+    let mut output =
+        String::from("<!DOCTYPE html>\n<html>\n<body>\n<pre style=\"font-family: monospace\">\n");
+    for x in 0..grid.height() {
+        for y in 0..grid.row_len(x) {
+            let letter = escape_html(grid.get((x, y)).unwrap());
+            match highlighted.get(&(x, y)) {
+                Some(word) => output.push_str(&format!(
+                    "<span style=\"color: {}; font-weight: bold\">{}</span>",
+                    HTML_COLORS[word % HTML_COLORS.len()],
+                    letter
+                )),
+                None => output.push_str(&format!("<span style=\"opacity: 0.3\">{}</span>", letter)),
+            }
+        }
+        output.push('\n');
+    }
+    output.push_str("</pre>\n</body>\n</html>\n");
+
+    output
+}
+
+fn escape_html(c: char) -> String {
+    match c {
+        '<' => "&lt;".to_owned(),
+        '>' => "&gt;".to_owned(),
+        '&' => "&amp;".to_owned(),
+        _ => c.to_string(),
+    }
+}
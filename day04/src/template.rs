@@ -0,0 +1,115 @@
+use crate::grid::Grid;
+
+const WILDCARD: char = '.';
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+// Rows are separated by `/` or newlines, e.g. "M.S/.A./M.S", with `.` matching
+// any letter
+impl From<&str> for Template {
+    fn from(s: &str) -> Self {
+        let mut cells: Vec<Vec<Option<char>>> = s
+            .split(['/', '\n'])
+            .map(|row| {
+                row.trim()
+                    .chars()
+                    .map(|c| if c == WILDCARD { None } else { Some(c) })
+                    .collect()
+            })
+            .collect();
+
+        let width = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in cells.iter_mut() {
+            row.resize(width, None);
+        }
+
+        Self { cells }
+    }
+}
+
+impl Template {
+    fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn width(&self) -> usize {
+        self.cells.first().map_or(0, |row| row.len())
+    }
+
+    pub fn rotated(&self) -> Self {
+        let mut cells = vec![vec![None; self.height()]; self.width()];
+        for (i, row) in self.cells.iter().enumerate() {
+            for (j, &cell) in row.iter().enumerate() {
+                cells[j][self.height() - 1 - i] = cell;
+            }
+        }
+
+        Self { cells }
+    }
+
+    pub fn reflected(&self) -> Self {
+        Self {
+            cells: self
+                .cells
+                .iter()
+                .map(|row| row.iter().rev().copied().collect())
+                .collect(),
+        }
+    }
+
+    // All distinct rotations and reflections, starting with the template itself
+    pub fn symmetries(&self) -> Vec<Self> {
+        let mut variants: Vec<Self> = Vec::new();
+
+        let mut current = self.clone();
+        for _ in 0..4 {
+            for variant in [current.clone(), current.reflected()] {
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
+            }
+
+            current = current.rotated();
+        }
+
+        variants
+    }
+
+    pub fn matches_at(&self, grid: &Grid, anchor: (usize, usize)) -> bool {
+        for (i, row) in self.cells.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                let Some(letter) = cell else {
+                    continue;
+                };
+
+                match grid.step(anchor, (i as isize, j as isize)) {
+                    Some(position) if grid.get(position) == Some(*letter) => {}
+                    _ => return false,
+                }
+            }
+        }
+
+        true
+    }
+}
+
+// Returns the top-left anchor of every placement where the template, or any of
+// its symmetries when requested, matches
+pub fn find_template(
+    grid: &Grid,
+    template: &Template,
+    with_symmetries: bool,
+) -> Vec<(usize, usize)> {
+    let variants = if with_symmetries {
+        template.symmetries()
+    } else {
+        vec![template.clone()]
+    };
+
+    grid.positions()
+        .filter(|&anchor| variants.iter().any(|v| v.matches_at(grid, anchor)))
+        .collect()
+}
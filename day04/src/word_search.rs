@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use crate::grid::{Grid, ALL_DIRECTIONS};
+
+#[derive(Debug, Clone)]
+pub struct WordSearchOptions {
+    pub directions: Vec<(isize, isize)>,
+    // A palindrome read backwards covers the same cells, so it would otherwise
+    // be reported once per reading direction
+    pub dedup_palindromes: bool,
+}
+
+impl Default for WordSearchOptions {
+    fn default() -> Self {
+        Self {
+            directions: ALL_DIRECTIONS.to_vec(),
+            dedup_palindromes: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordMatch {
+    pub start: (usize, usize),
+    pub direction: (isize, isize),
+}
+
+impl WordMatch {
+    pub fn cells(&self, grid: &Grid, len: usize) -> Vec<(usize, usize)> {
+        let mut cells = vec![self.start];
+        for _ in 1..len {
+            let last = *cells.last().unwrap();
+            cells.push(grid.step(last, self.direction).unwrap());
+        }
+
+        cells
+    }
+}
+
+pub fn find_word(grid: &Grid, word: &str, options: &WordSearchOptions) -> Vec<WordMatch> {
+    let letters: Vec<char> = word.chars().collect();
+    if letters.is_empty() {
+        return Vec::new();
+    }
+
+    let is_palindrome = letters.iter().eq(letters.iter().rev());
+    let mut seen_spans = HashSet::new();
+
+    let mut matches = Vec::new();
+    for start in grid.positions() {
+        for &direction in &options.directions {
+            let Some(end) = match_end(grid, &letters, start, direction) else {
+                continue;
+            };
+
+            if options.dedup_palindromes
+                && is_palindrome
+                && !seen_spans.insert((start.min(end), start.max(end)))
+            {
+                continue;
+            }
+
+            matches.push(WordMatch { start, direction });
+        }
+    }
+
+    matches
+}
+
+fn match_end(
+    grid: &Grid,
+    letters: &[char],
+    start: (usize, usize),
+    direction: (isize, isize),
+) -> Option<(usize, usize)> {
+    let mut position = start;
+    for (i, &letter) in letters.iter().enumerate() {
+        if i > 0 {
+            position = grid.step(position, direction)?;
+        }
+
+        if grid.get(position)? != letter {
+            return None;
+        }
+    }
+
+    Some(position)
+}
@@ -0,0 +1,162 @@
+use std::fmt::{self, Display};
+
+use crate::{
+    ordering::{reorder, reorder_by, CycleError},
+    rules::RuleIndex,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    // `rule.0|rule.1` is broken by rule.0 sitting at positions.0, after rule.1
+    // at positions.1
+    pub rule: (usize, usize),
+    pub positions: (usize, usize),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}|{} broken: {} at {}, {} at {}",
+            self.rule.0, self.rule.1, self.rule.0, self.positions.0, self.rule.1, self.positions.1
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    pub violations: Vec<Violation>,
+    pub moved_pages: Vec<usize>,
+    pub corrected: Vec<usize>,
+}
+
+pub fn violations(rules: &RuleIndex, update: &[usize]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (i, &later) in update.iter().enumerate() {
+        for (j, &earlier) in update[..i].iter().enumerate() {
+            if rules.must_precede(later, earlier) {
+                violations.push(Violation {
+                    rule: (later, earlier),
+                    positions: (i, j),
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+// Keeps the largest set of pages that are already in an order the rules
+// allow and moves every other page, which is the fewest moves possible. Any
+// set of pages with no pair inverted under the transitive closure of the rules
+// can stay put, with the rest fitted around them.
+pub fn explain(rules: &RuleIndex, update: &[usize]) -> Result<Correction, CycleError> {
+    reorder(rules, update)?;
+
+    let closure = transitive_closure(rules, update);
+    let kept = maximum_antichain(update.len(), |i, j| i < j && closure[j][i]);
+
+    let corrected = reorder_by(update, |i, j| {
+        rules.must_precede(update[i], update[j]) || (i < j && kept[i] && kept[j])
+    })?;
+
+    let moved_pages = update
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| !kept[i])
+        .map(|(_, &page)| page)
+        .collect();
+
+    Ok(Correction {
+        violations: violations(rules, update),
+        moved_pages,
+        corrected,
+    })
+}
+
+// closure[i][j] is set when page i must come before page j, directly or
+// through other pages of the update
+fn transitive_closure(rules: &RuleIndex, update: &[usize]) -> Vec<Vec<bool>> {
+    let mut closure: Vec<Vec<bool>> = update
+        .iter()
+        .map(|&a| update.iter().map(|&b| rules.must_precede(a, b)).collect())
+        .collect();
+
+    for k in 0..update.len() {
+        let through = closure[k].clone();
+        for row in closure.iter_mut().filter(|row| row[k]) {
+            for (reaches, &via) in row.iter_mut().zip(&through) {
+                *reaches |= via;
+            }
+        }
+    }
+
+    closure
+}
+
+// Largest set of elements no two of which are related by the strict partial
+// order `above`, via Dilworth's theorem: a maximum matching between elements
+// and the ones below them gives a minimum vertex cover (König), and the
+// elements the cover misses on both sides form the antichain.
+fn maximum_antichain(n: usize, above: impl Fn(usize, usize) -> bool) -> Vec<bool> {
+    let below: Vec<Vec<usize>> = (0..n)
+        .map(|i| (0..n).filter(|&j| above(i, j)).collect())
+        .collect();
+
+    // matched_to[j] is the element matched down to j
+    let mut matched_to = vec![None; n];
+    for i in 0..n {
+        augment(i, &below, &mut matched_to, &mut vec![false; n]);
+    }
+
+    let mut matched_from = vec![None; n];
+    for (j, &i) in matched_to.iter().enumerate() {
+        if let Some(i) = i {
+            matched_from[i] = Some(j);
+        }
+    }
+
+    // Alternating paths from unmatched elements on the upper side
+    let mut reached_upper: Vec<bool> = matched_from.iter().map(Option::is_none).collect();
+    let mut reached_lower = vec![false; n];
+    let mut pending: Vec<usize> = (0..n).filter(|&i| reached_upper[i]).collect();
+    while let Some(i) = pending.pop() {
+        for &j in &below[i] {
+            if reached_lower[j] {
+                continue;
+            }
+            reached_lower[j] = true;
+
+            if let Some(next) = matched_to[j].filter(|&next| !reached_upper[next]) {
+                reached_upper[next] = true;
+                pending.push(next);
+            }
+        }
+    }
+
+    (0..n)
+        .map(|i| reached_upper[i] && !reached_lower[i])
+        .collect()
+}
+
+// Kuhn's augmenting path search for the bipartite matching
+fn augment(
+    i: usize,
+    below: &[Vec<usize>],
+    matched_to: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for &j in &below[i] {
+        if visited[j] {
+            continue;
+        }
+        visited[j] = true;
+
+        if matched_to[j].is_none_or(|other| augment(other, below, matched_to, visited)) {
+            matched_to[j] = Some(i);
+            return true;
+        }
+    }
+
+    false
+}
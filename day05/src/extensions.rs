@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::rules::RuleIndex;
+
+const MAX_PAGES: usize = 64;
+// Down-closed sets kept in a single layer before giving up, sparse rules on a
+// few dozen pages would otherwise need up to 2^n of them
+const MAX_LAYER_SIZE: usize = 1 << 20;
+
+// Bitmask over the update's positions of the pages that must come before each
+// page
+fn predecessor_masks(rules: &RuleIndex, update: &[usize]) -> Vec<u64> {
+    update
+        .iter()
+        .map(|&page| {
+            update
+                .iter()
+                .enumerate()
+                .filter(|&(_, &other)| rules.must_precede(other, page))
+                .fold(0, |mask, (j, _)| mask | (1 << j))
+        })
+        .collect()
+}
+
+// The valid orders of one update, counted with a bitmask DP over the sets of
+// pages that can open a valid order
+pub struct Extensions<'a> {
+    update: &'a [usize],
+    predecessors: Vec<u64>,
+    // layers[k] maps every such set of k pages to the number of ways of
+    // ordering it, or None when that overflows a u128
+    layers: Vec<HashMap<u64, Option<u128>>>,
+}
+
+impl<'a> Extensions<'a> {
+    // None when the update is too large for the DP, either in pages or in how
+    // many sets a layer would need. Only down-closed sets are ever visited, so
+    // this stays small when the rules nearly fix the order.
+    pub fn new(rules: &RuleIndex, update: &'a [usize]) -> Option<Self> {
+        if update.len() > MAX_PAGES {
+            return None;
+        }
+
+        let predecessors = predecessor_masks(rules, update);
+
+        let mut layers = vec![HashMap::from([(0u64, Some(1u128))])];
+        for _ in 0..predecessors.len() {
+            let mut next: HashMap<u64, Option<u128>> = HashMap::new();
+            for (&mask, &count) in layers.last().unwrap() {
+                for (i, &required) in predecessors.iter().enumerate() {
+                    if mask & (1 << i) != 0 || required & !mask != 0 {
+                        continue;
+                    }
+
+                    let total = next.entry(mask | (1 << i)).or_insert(Some(0));
+                    *total = total.zip(count).and_then(|(a, b)| a.checked_add(b));
+                }
+
+                if next.len() > MAX_LAYER_SIZE {
+                    return None;
+                }
+            }
+
+            layers.push(next);
+        }
+
+        Some(Self {
+            update,
+            predecessors,
+            layers,
+        })
+    }
+
+    // None when the count overflows a u128
+    pub fn count(&self) -> Option<u128> {
+        // Cyclic rules leave the last layer empty
+        self.layers
+            .last()
+            .unwrap()
+            .values()
+            .next()
+            .copied()
+            .unwrap_or(Some(0))
+    }
+
+    // Pages that sit in the middle of at least one valid order. Any down-closed
+    // set can be completed, so a page fits there if it can follow such a set of
+    // half the update.
+    pub fn middle_candidates(&self) -> Vec<usize> {
+        if self.layers.last().unwrap().is_empty() {
+            return Vec::new();
+        }
+
+        let middle = self.update.len() / 2;
+        self.predecessors
+            .iter()
+            .enumerate()
+            .filter(|&(i, &required)| {
+                self.layers[middle]
+                    .keys()
+                    .any(|&mask| mask & (1 << i) == 0 && required & !mask == 0)
+            })
+            .map(|(i, _)| self.update[i])
+            .collect()
+    }
+
+    pub fn orderings(&self) -> Orderings<'a> {
+        Orderings {
+            update: self.update,
+            predecessors: self.predecessors.clone(),
+            order: Vec::new(),
+            used: 0,
+            next_candidate: vec![0],
+        }
+    }
+}
+
+// Lazily walks every valid order of the update by backtracking
+pub struct Orderings<'a> {
+    update: &'a [usize],
+    predecessors: Vec<u64>,
+    order: Vec<usize>,
+    used: u64,
+    // Next position to try at each depth, one more entry than the order has
+    next_candidate: Vec<usize>,
+}
+
+impl Orderings<'_> {
+    fn undo_last(&mut self) {
+        let i = self.order.pop().unwrap();
+        self.used &= !(1 << i);
+    }
+}
+
+impl Iterator for Orderings<'_> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The empty update has exactly one ordering, itself
+        if self.update.is_empty() {
+            return self.next_candidate.pop().map(|_| Vec::new());
+        }
+
+        loop {
+            let depth = self.order.len();
+            let start = *self.next_candidate.get(depth)?;
+
+            let candidate = (start..self.update.len())
+                .find(|&i| self.used & (1 << i) == 0 && self.predecessors[i] & !self.used == 0);
+
+            let Some(i) = candidate else {
+                self.next_candidate.pop();
+                if self.order.is_empty() {
+                    return None;
+                }
+
+                self.undo_last();
+                continue;
+            };
+
+            self.next_candidate[depth] = i + 1;
+            self.order.push(i);
+            self.used |= 1 << i;
+
+            if self.order.len() == self.update.len() {
+                let ordering = self.order.iter().map(|&i| self.update[i]).collect();
+                self.undo_last();
+                return Some(ordering);
+            }
+
+            self.next_candidate.push(0);
+        }
+    }
+}
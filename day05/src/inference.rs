@@ -0,0 +1,31 @@
+use std::collections::BTreeSet;
+
+use crate::{ordering::CycleError, rules::RuleIndex};
+
+// Rules only ever apply to the pages of a single update, so the only way for
+// rules to imply that a page comes right before its neighbour is a direct rule
+// between the two. The adjacent pairs of every update are therefore both
+// needed and enough, as long as no two updates order a pair differently.
+pub fn infer_rules(updates: &[Vec<usize>]) -> Result<Vec<(usize, usize)>, CycleError> {
+    let mut observed = RuleIndex::new(0);
+    for update in updates {
+        for (i, &earlier) in update.iter().enumerate() {
+            for &later in &update[i + 1..] {
+                if observed.must_precede(later, earlier) {
+                    return Err(CycleError {
+                        cycle: vec![earlier, later],
+                    });
+                }
+
+                observed.insert(earlier, later);
+            }
+        }
+    }
+
+    let rules: BTreeSet<(usize, usize)> = updates
+        .iter()
+        .flat_map(|update| update.windows(2).map(|pair| (pair[0], pair[1])))
+        .collect();
+
+    Ok(rules.into_iter().collect())
+}
@@ -1,4 +1,21 @@
-use std::{collections::HashMap, fs};
+mod explain;
+mod extensions;
+mod inference;
+mod ordering;
+mod rule_set;
+mod rules;
+
+use explain::explain;
+use extensions::Extensions;
+use inference::infer_rules;
+use ordering::reorder;
+use rule_set::{RuleSet, UpdateStatus};
+use rules::RuleIndex;
+use std::{
+    env,
+    fs::{self, File},
+    io::{BufWriter, Write},
+};
 
 fn main() {
     let input = fs::read_to_string("inputs/day05.txt")
@@ -8,82 +25,54 @@ fn main() {
 
     let mut sections = input.split("\n\n");
 
-    let mut rules = Vec::new();
-    for rule in sections.next().unwrap().lines() {
-        rules.push(rule.to_owned());
-    }
+    let rules = RuleIndex::from(sections.next().unwrap());
 
     let mut updates = Vec::new();
     for update in sections.next().unwrap().lines() {
-        updates.push(update.to_owned());
+        updates.push(parse_update(update));
     }
 
-    let mut rule_map = HashMap::new();
-
-    for rule in rules {
-        let mut parts = rule.split('|');
-        let first = parts.next().unwrap();
-        let second = parts.next().unwrap();
-
-        rule_map
-            .entry(first.to_owned())
-            .and_modify(|r: &mut Rule| r.disallow_before.push(second.to_owned()))
-            .or_insert(Rule {
-                disallow_before: vec![second.to_owned()],
-                disallow_after: Vec::new(),
-            });
-
-        rule_map
-            .entry(second.to_owned())
-            .and_modify(|r| r.disallow_after.push(first.to_owned()))
-            .or_insert(Rule {
-                disallow_before: Vec::new(),
-                disallow_after: vec![first.to_owned()],
-            });
+    let unordered_updates = part1(&rules, &updates);
+    part2(&rules, &unordered_updates);
+    explanations(&rules, &unordered_updates);
+    inferred_rules(&rules, &updates);
+    ambiguity(&rules, &updates);
+
+    if let Some(changes_path) = env::args().nth(1) {
+        rule_changes(RuleSet::new(rules.clone(), updates.clone()), &changes_path);
     }
+}
 
-    let unordered_updates = part1(&rule_map, &updates);
-    part2(&rule_map, &unordered_updates);
+fn parse_update(update: &str) -> Vec<usize> {
+    update
+        .split(',')
+        .map(|page| {
+            page.parse()
+                .expect("element in instruction should be a valid number")
+        })
+        .collect()
 }
 
-struct Rule {
-    disallow_before: Vec<String>,
-    disallow_after: Vec<String>,
+fn format_update(update: &[usize]) -> String {
+    update
+        .iter()
+        .map(|page| page.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
-fn part1(rule_map: &HashMap<String, Rule>, updates: &Vec<String>) -> Vec<String> {
+fn part1<'a>(rules: &RuleIndex, updates: &'a [Vec<usize>]) -> Vec<&'a [usize]> {
     println!("Day 05 - Part 1");
 
     let mut valid_middle_numbers_sum = 0;
     let mut unordered_updates = Vec::new();
-    'update_loop: for update in updates {
-        let nums: Vec<&str> = update.split(',').collect();
-
-        for (i, &num) in nums.iter().enumerate() {
-            let default_rule = Rule {
-                disallow_before: Vec::new(),
-                disallow_after: Vec::new(),
-            };
-
-            let num_rules = rule_map.get(num).unwrap_or(&default_rule);
-
-            for num_before in &nums[0..i] {
-                if num_rules.disallow_before.contains(&num_before.to_string()) {
-                    unordered_updates.push(update.clone());
-                    continue 'update_loop;
-                }
-            }
-            for num_after in &nums[i + 1..] {
-                if num_rules.disallow_after.contains(&num_after.to_string()) {
-                    unordered_updates.push(update.clone());
-                    continue 'update_loop;
-                }
-            }
+    for update in updates {
+        if !rules.is_ordered(update) {
+            unordered_updates.push(update.as_slice());
+            continue;
         }
 
-        valid_middle_numbers_sum += nums[nums.len() / 2]
-            .parse::<isize>()
-            .expect("element in instruction should be a valid number");
+        valid_middle_numbers_sum += update[update.len() / 2];
     }
 
     println!("Sum of valid middle numbers: {}", valid_middle_numbers_sum);
@@ -91,45 +80,152 @@ fn part1(rule_map: &HashMap<String, Rule>, updates: &Vec<String>) -> Vec<String>
     unordered_updates
 }
 
-fn part2(rule_map: &HashMap<String, Rule>, updates: &Vec<String>) {
+fn part2(rules: &RuleIndex, updates: &[&[usize]]) {
     println!("Day 05 - Part 2");
 
     let mut corrected_middle_numbers_sum = 0;
     for update in updates {
-        let mut nums: Vec<&str> = update.split(',').collect();
-
-        let default_rule = Rule {
-            disallow_before: Vec::new(),
-            disallow_after: Vec::new(),
+        let pages = match reorder(rules, update) {
+            Ok(pages) => pages,
+            Err(e) => {
+                println!("Skipping update {}: {}", format_update(update), e);
+                continue;
+            }
         };
 
-        let mut did_swap = true;
-        let mut last_elem = nums.len() - 1;
-        while did_swap {
-            did_swap = false;
+        corrected_middle_numbers_sum += pages[pages.len() / 2];
+    }
+
+    println!(
+        "Sum of corrected middle numbers: {}",
+        corrected_middle_numbers_sum
+    );
+}
 
-            for i in 0..last_elem {
-                let num_rules = rule_map.get(nums[i]).unwrap_or(&default_rule);
-                let next_num_rules = rule_map.get(nums[i + 1]).unwrap_or(&default_rule);
+fn explanations(rules: &RuleIndex, updates: &[&[usize]]) {
+    println!("Day 05 - Explanations");
 
-                if num_rules.disallow_after.contains(&nums[i + 1].to_owned())
-                    || next_num_rules.disallow_before.contains(&nums[i].to_owned())
-                {
-                    nums.swap(i, i + 1);
-                    did_swap = true;
+    let mut explanations_file = BufWriter::new(File::create("day05/explanations.txt").unwrap());
+
+    let mut violations = 0;
+    let mut moved_pages = 0;
+    for update in updates {
+        writeln!(explanations_file, "Before: {}", format_update(update)).unwrap();
+
+        match explain(rules, update) {
+            Ok(correction) => {
+                for violation in &correction.violations {
+                    writeln!(explanations_file, "  {}", violation).unwrap();
                 }
+                writeln!(
+                    explanations_file,
+                    "  Move: {}",
+                    format_update(&correction.moved_pages)
+                )
+                .unwrap();
+                writeln!(
+                    explanations_file,
+                    "After: {}",
+                    format_update(&correction.corrected)
+                )
+                .unwrap();
+
+                violations += correction.violations.len();
+                moved_pages += correction.moved_pages.len();
             }
+            Err(e) => writeln!(explanations_file, "  {}", e).unwrap(),
+        }
+    }
+    explanations_file.flush().unwrap();
+
+    println!("Violated rules: {}", violations);
+    println!("Pages moved: {}", moved_pages);
+}
 
-            last_elem = last_elem.saturating_sub(1);
+// Applies `+A|B` and `-A|B` lines one at a time, reporting the updates each
+// change affects
+fn rule_changes(mut rule_set: RuleSet, changes_path: &str) {
+    println!("Day 05 - Rule changes");
+
+    for change in fs::read_to_string(changes_path).unwrap().lines() {
+        let (first, second) = change[1..].split_once('|').unwrap();
+        let (a, b) = (first.parse().unwrap(), second.parse().unwrap());
+
+        let changed = match &change[..1] {
+            "+" => rule_set.add_rule(a, b),
+            "-" => rule_set.remove_rule(a, b),
+            _ => panic!("Unknown rule change {}", change),
+        };
+
+        println!("{}: {} updates changed", change, changed.len());
+        for i in changed {
+            let status = match rule_set.status(i) {
+                UpdateStatus::Valid => "valid".to_owned(),
+                UpdateStatus::Corrected(corrected) => {
+                    format!("corrected to {}", format_update(corrected))
+                }
+                UpdateStatus::NoValidOrder(e) => e.to_string(),
+            };
+
+            println!("  {}: {}", format_update(rule_set.update(i)), status);
         }
+    }
+}
+
+fn inferred_rules(rules: &RuleIndex, updates: &[Vec<usize>]) {
+    println!("Day 05 - Inferred rules");
+
+    let valid_updates: Vec<Vec<usize>> = updates
+        .iter()
+        .filter(|update| rules.is_ordered(update))
+        .cloned()
+        .collect();
 
-        corrected_middle_numbers_sum += nums[nums.len() / 2]
-            .parse::<isize>()
-            .expect("element in instruction should be a valid number");
+    match infer_rules(&valid_updates) {
+        Ok(inferred) => {
+            let mut rules_file = BufWriter::new(File::create("day05/inferred_rules.txt").unwrap());
+            for (a, b) in &inferred {
+                writeln!(rules_file, "{}|{}", a, b).unwrap();
+            }
+            rules_file.flush().unwrap();
+
+            println!("Rules inferred from valid updates: {}", inferred.len());
+        }
+        Err(e) => println!("Valid updates contradict each other: {}", e),
+    }
+}
+
+fn ambiguity(rules: &RuleIndex, updates: &[Vec<usize>]) {
+    println!("Day 05 - Ambiguity");
+
+    let mut ambiguous_middles = 0;
+    let mut unknown = 0;
+    let mut most_orderings = 0;
+    for update in updates {
+        let Some(extensions) = Extensions::new(rules, update) else {
+            unknown += 1;
+            continue;
+        };
+
+        most_orderings = most_orderings.max(extensions.count().unwrap_or(u128::MAX));
+
+        if extensions.middle_candidates().len() > 1 {
+            ambiguous_middles += 1;
+
+            println!(
+                "Ambiguous middle page in {}, for example:",
+                format_update(update)
+            );
+            for ordering in extensions.orderings().take(3) {
+                println!("  {}", format_update(&ordering));
+            }
+        }
     }
 
     println!(
-        "Sum of corrected middle numbers: {}",
-        corrected_middle_numbers_sum
+        "Updates with an ambiguous middle page: {}",
+        ambiguous_middles
     );
+    println!("Updates too large to check: {}", unknown);
+    println!("Most valid orderings of an update: {}", most_orderings);
 }
@@ -0,0 +1,99 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+};
+
+use crate::rules::RuleIndex;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleError {
+    pub cycle: Vec<usize>,
+}
+
+impl Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no valid order, rules form a cycle:")?;
+        for page in &self.cycle {
+            write!(f, " {} ->", page)?;
+        }
+        write!(f, " {}", self.cycle[0])
+    }
+}
+
+// Kahn's algorithm over the rules induced on the update's pages. Ties are
+// broken by the pages' original positions, so already ordered runs stay put.
+pub fn reorder(rules: &RuleIndex, pages: &[usize]) -> Result<Vec<usize>, CycleError> {
+    reorder_by(pages, |i, j| rules.must_precede(pages[i], pages[j]))
+}
+
+// Same as `reorder`, with constraints given as `precedes(i, j)` over positions
+// in `pages`, for callers adding their own on top of the rules
+pub fn reorder_by(
+    pages: &[usize],
+    precedes: impl Fn(usize, usize) -> bool,
+) -> Result<Vec<usize>, CycleError> {
+    let mut successors = vec![Vec::new(); pages.len()];
+    let mut in_degrees = vec![0; pages.len()];
+    for (i, next) in successors.iter_mut().enumerate() {
+        for (j, in_degree) in in_degrees.iter_mut().enumerate() {
+            if i != j && precedes(i, j) {
+                next.push(j);
+                *in_degree += 1;
+            }
+        }
+    }
+
+    let mut ready: VecDeque<usize> = (0..pages.len()).filter(|&i| in_degrees[i] == 0).collect();
+    let mut order = Vec::with_capacity(pages.len());
+    while let Some(i) = ready.pop_front() {
+        order.push(pages[i]);
+
+        for &j in &successors[i] {
+            in_degrees[j] -= 1;
+            if in_degrees[j] == 0 {
+                let position = ready.partition_point(|&k| k < j);
+                ready.insert(position, j);
+            }
+        }
+    }
+
+    if order.len() < pages.len() {
+        return Err(CycleError {
+            cycle: find_cycle(&successors, &in_degrees)
+                .into_iter()
+                .map(|i| pages[i])
+                .collect(),
+        });
+    }
+
+    Ok(order)
+}
+
+// Every page Kahn's algorithm left behind still has a predecessor that was
+// left behind too, so walking predecessors from any of them must loop.
+fn find_cycle(successors: &[Vec<usize>], in_degrees: &[usize]) -> Vec<usize> {
+    let remaining = |i: usize| in_degrees[i] > 0;
+
+    let mut predecessors = vec![Vec::new(); successors.len()];
+    for (i, next) in successors.iter().enumerate() {
+        for &j in next {
+            predecessors[j].push(i);
+        }
+    }
+
+    let mut current = (0..successors.len()).find(|&i| remaining(i)).unwrap();
+    let mut walk = Vec::new();
+    while !walk.contains(&current) {
+        walk.push(current);
+        current = *predecessors[current]
+            .iter()
+            .find(|&&p| remaining(p))
+            .unwrap();
+    }
+
+    let start = walk.iter().position(|&i| i == current).unwrap();
+    let mut cycle = walk[start..].to_vec();
+    // Predecessor walks run against the rules, flip to read in rule order
+    cycle.reverse();
+    cycle
+}
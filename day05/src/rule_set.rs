@@ -0,0 +1,123 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::{
+    ordering::{reorder, CycleError},
+    rules::RuleIndex,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateStatus {
+    Valid,
+    Corrected(Vec<usize>),
+    NoValidOrder(CycleError),
+}
+
+impl UpdateStatus {
+    fn of(rules: &RuleIndex, update: &[usize]) -> Self {
+        if rules.is_ordered(update) {
+            return Self::Valid;
+        }
+
+        match reorder(rules, update) {
+            Ok(corrected) => Self::Corrected(corrected),
+            Err(e) => Self::NoValidOrder(e),
+        }
+    }
+}
+
+// Rules that can change at runtime. A rule only involves two pages, so only
+// updates containing both are revalidated when it is added or removed.
+pub struct RuleSet {
+    rules: RuleIndex,
+    updates: Vec<Vec<usize>>,
+    statuses: Vec<UpdateStatus>,
+    updates_by_page: HashMap<usize, Vec<usize>>,
+}
+
+impl RuleSet {
+    pub fn new(rules: RuleIndex, updates: Vec<Vec<usize>>) -> Self {
+        let mut updates_by_page: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (i, update) in updates.iter().enumerate() {
+            for &page in update {
+                let with_page = updates_by_page.entry(page).or_default();
+                if with_page.last() != Some(&i) {
+                    with_page.push(i);
+                }
+            }
+        }
+
+        let statuses = updates
+            .iter()
+            .map(|update| UpdateStatus::of(&rules, update))
+            .collect();
+
+        Self {
+            rules,
+            updates,
+            statuses,
+            updates_by_page,
+        }
+    }
+
+    pub fn update(&self, i: usize) -> &[usize] {
+        &self.updates[i]
+    }
+
+    pub fn status(&self, i: usize) -> &UpdateStatus {
+        &self.statuses[i]
+    }
+
+    // Both return the indexes of updates whose validity or corrected order
+    // changed
+    pub fn add_rule(&mut self, a: usize, b: usize) -> Vec<usize> {
+        if self.rules.must_precede(a, b) {
+            return Vec::new();
+        }
+
+        self.rules.insert(a, b);
+        self.revalidate(a, b)
+    }
+
+    pub fn remove_rule(&mut self, a: usize, b: usize) -> Vec<usize> {
+        if !self.rules.must_precede(a, b) {
+            return Vec::new();
+        }
+
+        self.rules.remove(a, b);
+        self.revalidate(a, b)
+    }
+
+    fn revalidate(&mut self, a: usize, b: usize) -> Vec<usize> {
+        let (Some(with_a), Some(with_b)) =
+            (self.updates_by_page.get(&a), self.updates_by_page.get(&b))
+        else {
+            return Vec::new();
+        };
+
+        // Both lists are in ascending order, so they intersect in one merge
+        let mut with_both = Vec::new();
+        let (mut x, mut y) = (0, 0);
+        while x < with_a.len() && y < with_b.len() {
+            match with_a[x].cmp(&with_b[y]) {
+                Ordering::Less => x += 1,
+                Ordering::Greater => y += 1,
+                Ordering::Equal => {
+                    with_both.push(with_a[x]);
+                    x += 1;
+                    y += 1;
+                }
+            }
+        }
+
+        let mut changed = Vec::new();
+        for i in with_both {
+            let status = UpdateStatus::of(&self.rules, &self.updates[i]);
+            if status != self.statuses[i] {
+                self.statuses[i] = status;
+                changed.push(i);
+            }
+        }
+
+        changed
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+const WORD_BITS: usize = 64;
+
+// Dense adjacency matrix of `A|B` rules, one bit per ordered pair, so "must A
+// precede B" is a single lookup. Page ids are interned to dense indexes, so
+// the matrix is sized by how many distinct pages the rules mention rather
+// than by the largest id.
+#[derive(Debug, Clone)]
+pub struct RuleIndex {
+    indexes: HashMap<usize, u32>,
+    size: usize,
+    bits: Vec<u64>,
+}
+
+impl From<&str> for RuleIndex {
+    fn from(s: &str) -> Self {
+        let rules: Vec<(usize, usize)> = s
+            .lines()
+            .map(|rule| {
+                let (first, second) = rule.split_once('|').unwrap();
+                (first.parse().unwrap(), second.parse().unwrap())
+            })
+            .collect();
+
+        let mut index = Self::new(0);
+        for &(a, b) in &rules {
+            index.intern(a);
+            index.intern(b);
+        }
+        index.grow(index.indexes.len());
+
+        for (a, b) in rules {
+            index.insert(a, b);
+        }
+
+        index
+    }
+}
+
+impl RuleIndex {
+    pub fn new(size: usize) -> Self {
+        Self {
+            indexes: HashMap::new(),
+            size,
+            bits: vec![0; (size * size).div_ceil(WORD_BITS)],
+        }
+    }
+
+    // Dense index of the page, assigning the next free one to a new page
+    fn intern(&mut self, page: usize) -> usize {
+        let next = self.indexes.len() as u32;
+        *self.indexes.entry(page).or_insert(next) as usize
+    }
+
+    fn bit(&self, a: usize, b: usize) -> Option<usize> {
+        let (a, b) = (*self.indexes.get(&a)?, *self.indexes.get(&b)?);
+        Some(a as usize * self.size + b as usize)
+    }
+
+    pub fn insert(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.intern(a), self.intern(b));
+        if self.indexes.len() > self.size {
+            self.grow(self.indexes.len().max(self.size * 2));
+        }
+
+        let bit = a * self.size + b;
+        self.bits[bit / WORD_BITS] |= 1 << (bit % WORD_BITS);
+    }
+
+    pub fn remove(&mut self, a: usize, b: usize) {
+        let Some(bit) = self.bit(a, b) else {
+            return;
+        };
+
+        self.bits[bit / WORD_BITS] &= !(1 << (bit % WORD_BITS));
+    }
+
+    pub fn must_precede(&self, a: usize, b: usize) -> bool {
+        let Some(bit) = self.bit(a, b) else {
+            return false;
+        };
+
+        self.bits[bit / WORD_BITS] & (1 << (bit % WORD_BITS)) != 0
+    }
+
+    pub fn is_ordered(&self, pages: &[usize]) -> bool {
+        for (i, &later) in pages.iter().enumerate() {
+            for &earlier in &pages[..i] {
+                if self.must_precede(later, earlier) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    // Resizes the matrix, keeping every page's dense index
+    fn grow(&mut self, size: usize) {
+        let mut bits = vec![0; (size * size).div_ceil(WORD_BITS)];
+        for a in 0..self.size {
+            for b in 0..self.size {
+                let old = a * self.size + b;
+                if self.bits[old / WORD_BITS] & (1 << (old % WORD_BITS)) != 0 {
+                    let new = a * size + b;
+                    bits[new / WORD_BITS] |= 1 << (new % WORD_BITS);
+                }
+            }
+        }
+
+        self.size = size;
+        self.bits = bits;
+    }
+}
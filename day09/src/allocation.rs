@@ -0,0 +1,279 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, BinaryHeap},
+    fmt::{self, Display},
+};
+
+use crate::disk_map::{DiskMap, Extent, Span};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactionMetrics {
+    pub free_spans: usize,
+    pub largest_free_span: usize,
+    pub files_moved: usize,
+    pub blocks_moved: usize,
+}
+
+impl Display for CompactionMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} free spans, largest {} blocks, {} files moved, {} blocks moved",
+            self.free_spans, self.largest_free_span, self.files_moved, self.blocks_moved
+        )
+    }
+}
+
+// Free spans kept by start, so released space can merge with its neighbours,
+// and as a min-heap of starts per span size, so the leftmost span of each size
+// is a peek. A removed span's start stays in its heap until it reaches the
+// top, where it is dropped, so every heap's top is a free span.
+#[derive(Debug, Clone)]
+pub struct FreeSpans {
+    starts_by_size: BTreeMap<usize, BinaryHeap<Reverse<usize>>>,
+    lens_by_start: BTreeMap<usize, usize>,
+    // Starts in order per span size, only built once a search begins part way
+    // along the disk, which the heaps can't do
+    sorted_starts_by_size: Option<BTreeMap<usize, BTreeSet<usize>>>,
+}
+
+impl FreeSpans {
+    pub fn new() -> Self {
+        Self {
+            starts_by_size: BTreeMap::new(),
+            lens_by_start: BTreeMap::new(),
+            sorted_starts_by_size: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lens_by_start.len()
+    }
+
+    pub fn largest(&self) -> usize {
+        self.starts_by_size.keys().next_back().copied().unwrap_or(0)
+    }
+
+    pub fn starting_at(&self, start: usize) -> Option<Span> {
+        self.lens_by_start
+            .get(&start)
+            .map(|&len| Span { start, len })
+    }
+
+    pub fn first(&self) -> Option<Span> {
+        self.lens_by_start
+            .iter()
+            .next()
+            .map(|(&start, &len)| Span { start, len })
+    }
+
+    pub fn last(&self) -> Option<Span> {
+        self.lens_by_start
+            .iter()
+            .next_back()
+            .map(|(&start, &len)| Span { start, len })
+    }
+
+    pub fn leftmost_fitting(&self, len: usize) -> Option<Span> {
+        self.fitting(len, usize::MAX).min_by_key(|s| s.start)
+    }
+
+    pub fn insert(&mut self, span: Span) {
+        if span.len == 0 {
+            return;
+        }
+
+        self.starts_by_size
+            .entry(span.len)
+            .or_default()
+            .push(Reverse(span.start));
+        self.lens_by_start.insert(span.start, span.len);
+        if let Some(sorted) = &mut self.sorted_starts_by_size {
+            sorted.entry(span.len).or_default().insert(span.start);
+        }
+    }
+
+    pub fn remove(&mut self, span: Span) {
+        self.lens_by_start.remove(&span.start);
+
+        let starts = self.starts_by_size.get_mut(&span.len).unwrap();
+        while let Some(&Reverse(start)) = starts.peek() {
+            if self.lens_by_start.get(&start) == Some(&span.len) {
+                break;
+            }
+            starts.pop();
+        }
+        if starts.is_empty() {
+            self.starts_by_size.remove(&span.len);
+        }
+
+        if let Some(sorted) = &mut self.sorted_starts_by_size {
+            let starts = sorted.get_mut(&span.len).unwrap();
+            starts.remove(&span.start);
+            if starts.is_empty() {
+                sorted.remove(&span.len);
+            }
+        }
+    }
+
+    // Frees a span, merging it with free spans directly before and after it
+    pub fn release(&mut self, mut span: Span) {
+        if let Some((&start, &len)) = self.lens_by_start.range(..span.start).next_back() {
+            if start + len == span.start {
+                self.remove(Span { start, len });
+                span = Span {
+                    start,
+                    len: len + span.len,
+                };
+            }
+        }
+
+        let end = span.start + span.len;
+        if let Some(&len) = self.lens_by_start.get(&end) {
+            self.remove(Span { start: end, len });
+            span.len += len;
+        }
+
+        self.insert(span);
+    }
+
+    // Spans big enough for the extent and left of it, the leftmost one of each
+    // size
+    fn candidates(&self, extent: &Extent) -> impl Iterator<Item = Span> + '_ {
+        self.fitting(extent.len, extent.start)
+    }
+
+    // The leftmost span big enough for the extent, starting at or after `from`
+    // and left of the extent
+    fn leftmost_from(&mut self, extent: &Extent, from: usize) -> Option<Span> {
+        let lens_by_start = &self.lens_by_start;
+        let sorted = self.sorted_starts_by_size.get_or_insert_with(|| {
+            let mut sorted: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+            for (&start, &len) in lens_by_start {
+                sorted.entry(len).or_default().insert(start);
+            }
+
+            sorted
+        });
+
+        let from = from.min(extent.start);
+        sorted
+            .range(extent.len..)
+            .filter_map(|(&size, starts)| {
+                starts
+                    .range(from..extent.start)
+                    .next()
+                    .map(|&start| Span { start, len: size })
+            })
+            .min_by_key(|s| s.start)
+    }
+
+    // Spans of at least `len` starting before `before`, the leftmost one of
+    // each size
+    fn fitting(&self, len: usize, before: usize) -> impl Iterator<Item = Span> + '_ {
+        self.starts_by_size
+            .range(len..)
+            .filter_map(move |(&size, starts)| {
+                let &Reverse(start) = starts.peek()?;
+                (start < before).then_some(Span { start, len: size })
+            })
+    }
+}
+
+pub trait AllocationStrategy {
+    fn name(&self) -> &'static str;
+
+    fn compact(&mut self, disk_map: &mut DiskMap) -> CompactionMetrics;
+}
+
+pub struct FirstFit;
+
+impl AllocationStrategy for FirstFit {
+    fn name(&self) -> &'static str {
+        "first-fit"
+    }
+
+    fn compact(&mut self, disk_map: &mut DiskMap) -> CompactionMetrics {
+        disk_map.compact_files(|free, extent| free.candidates(extent).min_by_key(|s| s.start))
+    }
+}
+
+pub struct BestFit;
+
+impl AllocationStrategy for BestFit {
+    fn name(&self) -> &'static str {
+        "best-fit"
+    }
+
+    fn compact(&mut self, disk_map: &mut DiskMap) -> CompactionMetrics {
+        disk_map.compact_files(|free, extent| free.candidates(extent).next())
+    }
+}
+
+pub struct WorstFit;
+
+impl AllocationStrategy for WorstFit {
+    fn name(&self) -> &'static str {
+        "worst-fit"
+    }
+
+    fn compact(&mut self, disk_map: &mut DiskMap) -> CompactionMetrics {
+        disk_map.compact_files(|free, extent| free.candidates(extent).last())
+    }
+}
+
+// Resumes searching from where the previous file was placed, wrapping around to
+// the start of the disk when nothing fits further on
+pub struct NextFit {
+    cursor: usize,
+}
+
+impl NextFit {
+    pub fn new() -> Self {
+        Self { cursor: 0 }
+    }
+}
+
+impl AllocationStrategy for NextFit {
+    fn name(&self) -> &'static str {
+        "next-fit"
+    }
+
+    fn compact(&mut self, disk_map: &mut DiskMap) -> CompactionMetrics {
+        let cursor = &mut self.cursor;
+
+        disk_map.compact_files(|free, extent| {
+            let span = free
+                .leftmost_from(extent, *cursor)
+                .or_else(|| free.candidates(extent).min_by_key(|s| s.start))?;
+
+            *cursor = span.start + extent.len;
+            Some(span)
+        })
+    }
+}
+
+pub struct BlockLevel;
+
+impl AllocationStrategy for BlockLevel {
+    fn name(&self) -> &'static str {
+        "block-level"
+    }
+
+    fn compact(&mut self, disk_map: &mut DiskMap) -> CompactionMetrics {
+        disk_map.compact_blocks()
+    }
+}
+
+pub fn strategy_by_name(name: &str) -> Option<Box<dyn AllocationStrategy>> {
+    let strategy: Box<dyn AllocationStrategy> = match name {
+        "first-fit" => Box::new(FirstFit),
+        "best-fit" => Box::new(BestFit),
+        "worst-fit" => Box::new(WorstFit),
+        "next-fit" => Box::new(NextFit::new()),
+        "block-level" => Box::new(BlockLevel),
+        _ => return None,
+    };
+
+    Some(strategy)
+}
@@ -0,0 +1,484 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+};
+
+use crate::allocation::{CompactionMetrics, FreeSpans};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extent {
+    pub file_id: usize,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Extent {
+    pub fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    // Sum of position * file id over the extent's blocks
+    pub fn checksum(&self) -> usize {
+        self.file_id * (self.len * self.start + self.len * self.len.saturating_sub(1) / 2)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub len: usize,
+}
+
+// A disk as runs of blocks instead of one entry per block. Files are stored as
+// extents, and free space is whatever lies between them up to `size`. Extents
+// are indexed by position, and by file once a file is first looked up, free
+// space by position and size, and the checksum is kept up to date as extents
+// move, so no operation has to walk the whole disk.
+#[derive(Debug, Clone)]
+pub struct DiskMap {
+    extents: BTreeMap<usize, Extent>,
+    starts_by_file: Option<HashMap<usize, BTreeSet<usize>>>,
+    free: FreeSpans,
+    size: usize,
+    checksum: usize,
+    next_file_id: usize,
+}
+
+impl From<&str> for DiskMap {
+    fn from(s: &str) -> Self {
+        let mut disk_map = Self {
+            extents: BTreeMap::new(),
+            starts_by_file: None,
+            free: FreeSpans::new(),
+            size: 0,
+            checksum: 0,
+            next_file_id: s.len().div_ceil(2),
+        };
+
+        for (i, c) in s.chars().enumerate() {
+            let len = c.to_digit(10).unwrap() as usize;
+            let start = disk_map.size;
+            disk_map.size += len;
+
+            if i % 2 == 0 {
+                disk_map.add_extent(Extent {
+                    file_id: i / 2,
+                    start,
+                    len,
+                });
+            } else {
+                disk_map.free.release(Span { start, len });
+            }
+        }
+
+        disk_map
+    }
+}
+
+impl DiskMap {
+    pub fn checksum(&self) -> usize {
+        self.checksum
+    }
+
+    // File id of every block, `None` for free ones
+    pub fn blocks(&self) -> Vec<Option<usize>> {
+        let mut blocks = vec![None; self.size];
+        for extent in self.extents.values() {
+            blocks[extent.start..extent.end()].fill(Some(extent.file_id));
+        }
+
+        blocks
+    }
+
+    fn add_extent(&mut self, extent: Extent) {
+        if extent.len == 0 {
+            return;
+        }
+
+        self.checksum += extent.checksum();
+        self.extents.insert(extent.start, extent);
+        if let Some(starts_by_file) = &mut self.starts_by_file {
+            starts_by_file
+                .entry(extent.file_id)
+                .or_default()
+                .insert(extent.start);
+        }
+    }
+
+    fn remove_extent(&mut self, start: usize) -> Extent {
+        let extent = self.extents.remove(&start).unwrap();
+        self.checksum -= extent.checksum();
+
+        // A deleted file is already gone from the index
+        if let Some(starts) = self
+            .starts_by_file
+            .as_mut()
+            .and_then(|starts_by_file| starts_by_file.get_mut(&extent.file_id))
+        {
+            starts.remove(&start);
+        }
+
+        extent
+    }
+
+    // Extent starts of every file, building the index on first use. A file
+    // created empty has no starts but is still listed.
+    fn files(&mut self) -> &mut HashMap<usize, BTreeSet<usize>> {
+        let extents = &self.extents;
+        self.starts_by_file.get_or_insert_with(|| {
+            let mut starts_by_file: HashMap<usize, BTreeSet<usize>> = HashMap::new();
+            for extent in extents.values() {
+                starts_by_file
+                    .entry(extent.file_id)
+                    .or_default()
+                    .insert(extent.start);
+            }
+
+            starts_by_file
+        })
+    }
+
+    // Takes the first `len` blocks of a free span
+    fn claim(&mut self, span: Span, len: usize) {
+        self.free.remove(span);
+        self.free.insert(Span {
+            start: span.start + len,
+            len: span.len - len,
+        });
+    }
+
+    // Places `len` blocks of a file in the leftmost free span that fits,
+    // otherwise at the end of the disk, taking any free space already there
+    fn allocate(&mut self, file_id: usize, len: usize) {
+        let start = match self.free.leftmost_fitting(len) {
+            Some(span) => {
+                self.claim(span, len);
+                span.start
+            }
+            None => match self.free.last().filter(|s| s.start + s.len == self.size) {
+                Some(trailing) => {
+                    self.free.remove(trailing);
+                    self.size = trailing.start + len;
+                    trailing.start
+                }
+                None => {
+                    self.size += len;
+                    self.size - len
+                }
+            },
+        };
+
+        self.add_extent(Extent {
+            file_id,
+            start,
+            len,
+        });
+    }
+
+    // Returns the new file's id
+    pub fn create(&mut self, len: usize) -> usize {
+        let file_id = self.next_file_id;
+        self.next_file_id += 1;
+
+        if len > 0 {
+            self.allocate(file_id, len);
+        } else {
+            self.files().insert(file_id, BTreeSet::new());
+        }
+
+        file_id
+    }
+
+    // Returns false if there is no such file
+    pub fn delete(&mut self, file_id: usize) -> bool {
+        let Some(starts) = self.files().remove(&file_id) else {
+            return false;
+        };
+
+        for start in starts {
+            let extent = self.remove_extent(start);
+            self.free.release(Span {
+                start: extent.start,
+                len: extent.len,
+            });
+        }
+
+        true
+    }
+
+    // Grows the file's last extent into any free space right after it, or past
+    // the end of the disk, and allocates whatever doesn't fit there as a new
+    // extent, which for an empty file is all of it. Returns false if there is
+    // no such file.
+    pub fn append(&mut self, file_id: usize, len: usize) -> bool {
+        let Some(starts) = self.files().get(&file_id) else {
+            return false;
+        };
+
+        let Some(&start) = starts.last() else {
+            if len > 0 {
+                self.allocate(file_id, len);
+            }
+            return true;
+        };
+
+        let mut extent = self.remove_extent(start);
+        let grown = match self.free.starting_at(extent.end()) {
+            Some(span) if span.start + span.len == self.size && span.len < len => {
+                self.free.remove(span);
+                self.size = span.start + len;
+                len
+            }
+            Some(span) => {
+                let grown = len.min(span.len);
+                self.claim(span, grown);
+                grown
+            }
+            None if extent.end() == self.size => {
+                self.size += len;
+                len
+            }
+            None => 0,
+        };
+        extent.len += grown;
+        self.add_extent(extent);
+
+        if len > grown {
+            self.allocate(file_id, len - grown);
+        }
+
+        true
+    }
+
+    fn metrics(&self, files_moved: usize, blocks_moved: usize) -> CompactionMetrics {
+        CompactionMetrics {
+            free_spans: self.free.len(),
+            largest_free_span: self.free.largest(),
+            files_moved,
+            blocks_moved,
+        }
+    }
+
+    // Writes the layout back in the dense digit format `From<&str>` reads.
+    // That format can't carry file ids, so every extent becomes a new file
+    // numbered by position, and runs longer than 9 are split by zero-length
+    // entries of the other kind, which makes a long file several files.
+    pub fn to_dense(&self) -> String {
+        let mut dense = String::new();
+        let mut position = 0;
+        for extent in self.extents.values() {
+            push_run(&mut dense, extent.start - position, false);
+            push_run(&mut dense, extent.len, true);
+            position = extent.end();
+        }
+        push_run(&mut dense, self.size - position, false);
+
+        dense
+    }
+
+    // Moves blocks one at a time from the end of the disk into the leftmost
+    // free block, splitting files as needed
+    pub fn compact_blocks(&mut self) -> CompactionMetrics {
+        let mut files_moved = HashSet::new();
+        let mut blocks_moved = 0;
+
+        while let (Some(span), Some(&extent)) =
+            (self.free.first(), self.extents.values().next_back())
+        {
+            if span.start >= extent.start {
+                break;
+            }
+
+            let moved = span.len.min(extent.len);
+            let mut extent = self.remove_extent(extent.start);
+            extent.len -= moved;
+            self.add_extent(extent);
+
+            self.claim(span, moved);
+            self.free.release(Span {
+                start: extent.end(),
+                len: moved,
+            });
+            self.add_extent(Extent {
+                file_id: extent.file_id,
+                start: span.start,
+                len: moved,
+            });
+
+            files_moved.insert(extent.file_id);
+            blocks_moved += moved;
+        }
+
+        self.metrics(files_moved.len(), blocks_moved)
+    }
+
+    // Moves every whole extent, highest file id first, into the free span
+    // picked by `choose`, if any. A fragmented file moves one extent at a
+    // time, rightmost first. Space an extent leaves behind right of all the
+    // extents still to move can never be picked, so it is only released once
+    // they are done, which keeps the free space index small meanwhile.
+    pub fn compact_files(
+        &mut self,
+        mut choose: impl FnMut(&mut FreeSpans, &Extent) -> Option<Span>,
+    ) -> CompactionMetrics {
+        let mut order: Vec<Extent> = self.extents.values().copied().collect();
+        order.sort_by_key(|e| (Reverse(e.file_id), Reverse(e.start)));
+
+        let mut rightmost_pending = vec![0; order.len() + 1];
+        for (i, extent) in order.iter().enumerate().rev() {
+            rightmost_pending[i] = rightmost_pending[i + 1].max(extent.start);
+        }
+
+        let mut files_moved = HashSet::new();
+        let mut blocks_moved = 0;
+        let mut deferred = Vec::new();
+        for (i, extent) in order.into_iter().enumerate() {
+            let Some(span) = choose(&mut self.free, &extent) else {
+                continue;
+            };
+
+            self.remove_extent(extent.start);
+            self.claim(span, extent.len);
+            self.add_extent(Extent {
+                start: span.start,
+                ..extent
+            });
+
+            let vacated = Span {
+                start: extent.start,
+                len: extent.len,
+            };
+            if extent.start < rightmost_pending[i + 1] {
+                self.free.release(vacated);
+            } else {
+                deferred.push(vacated);
+            }
+
+            files_moved.insert(extent.file_id);
+            blocks_moved += extent.len;
+        }
+
+        for span in deferred {
+            self.free.release(span);
+        }
+
+        self.metrics(files_moved.len(), blocks_moved)
+    }
+}
+
+// Entries alternate file, free, file, ..., so a run of the kind not due next
+// is preceded by a zero-length entry of the other kind
+fn push_run(dense: &mut String, mut len: usize, file: bool) {
+    while len > 0 {
+        if dense.len().is_multiple_of(2) != file {
+            dense.push('0');
+        }
+
+        let digit = len.min(9);
+        dense.push(char::from_digit(digit as u32, 10).unwrap());
+        len -= digit;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocation::{AllocationStrategy, FirstFit};
+
+    const EXAMPLE: &str = "2333133121414131402";
+
+    fn occupied(disk_map: &DiskMap) -> Vec<bool> {
+        disk_map.blocks().iter().map(Option::is_some).collect()
+    }
+
+    // Serializing renumbers files, so the parsed copy is compared by which
+    // blocks are used, and must serialize back to the same digits
+    fn assert_round_trips(disk_map: &DiskMap) -> String {
+        let dense = disk_map.to_dense();
+        let parsed = DiskMap::from(dense.as_str());
+
+        assert_eq!(occupied(&parsed), occupied(disk_map));
+        assert_eq!(parsed.to_dense(), dense);
+
+        dense
+    }
+
+    #[test]
+    fn original_layout_round_trips() {
+        let disk_map = DiskMap::from(EXAMPLE);
+
+        assert_eq!(assert_round_trips(&disk_map), EXAMPLE);
+    }
+
+    #[test]
+    fn block_compacted_layout_round_trips() {
+        let mut disk_map = DiskMap::from(EXAMPLE);
+        disk_map.compact_blocks();
+
+        // 0099811188827773336446555566.............., where the last two 6s
+        // were moved separately and stay separate files
+        assert_eq!(
+            assert_round_trips(&disk_map),
+            "202010303010303010201040101905"
+        );
+    }
+
+    #[test]
+    fn first_fit_layout_round_trips() {
+        let mut disk_map = DiskMap::from(EXAMPLE);
+        FirstFit.compact(&mut disk_map);
+
+        // 00992111777.44.333....5555.6666.....8888..
+        assert_eq!(assert_round_trips(&disk_map), "20201030312134414542");
+    }
+
+    #[test]
+    fn long_runs_are_split_with_zero_length_entries() {
+        let free = DiskMap::from("19091");
+        assert_eq!(assert_round_trips(&free), "19091");
+
+        let mut file = DiskMap::from("");
+        file.create(12);
+        assert_eq!(assert_round_trips(&file), "903");
+    }
+
+    #[test]
+    fn empty_file_grows_on_append() {
+        let mut disk_map = DiskMap::from("1213");
+        let file_id = disk_map.create(0);
+        assert_eq!(disk_map.blocks().iter().flatten().count(), 2);
+
+        // Only the trailing free span fits all three blocks
+        assert!(disk_map.append(file_id, 3));
+        assert_eq!(
+            disk_map.blocks(),
+            [
+                Some(0),
+                None,
+                None,
+                Some(1),
+                Some(file_id),
+                Some(file_id),
+                Some(file_id)
+            ]
+        );
+
+        assert!(disk_map.delete(file_id));
+        assert!(!disk_map.delete(file_id));
+        assert!(!disk_map.append(file_id, 1));
+    }
+
+    #[test]
+    fn trailing_free_run_round_trips() {
+        let disk_map = DiskMap::from("1213");
+
+        assert_eq!(assert_round_trips(&disk_map), "1213");
+    }
+
+    #[test]
+    fn empty_map_round_trips() {
+        let disk_map = DiskMap::from("");
+
+        assert_eq!(assert_round_trips(&disk_map), "");
+    }
+}
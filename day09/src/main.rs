@@ -1,7 +1,13 @@
-use std::{
-    fmt::{self, Error, Formatter},
-    fs, iter,
-};
+mod allocation;
+mod disk_map;
+mod render;
+mod simulator;
+
+use allocation::{AllocationStrategy, BestFit, BlockLevel, FirstFit, NextFit, WorstFit};
+use disk_map::DiskMap;
+use render::{render_ansi, render_columns};
+use simulator::{apply, Operation};
+use std::{env, fs};
 
 fn main() {
     let input = fs::read_to_string("inputs/day09.txt")
@@ -9,153 +15,84 @@ fn main() {
         .trim()
         .to_owned();
 
-    part1(&input);
-    part2(&input);
-}
+    let disk_map = DiskMap::from(input.as_str());
 
-fn part1(input: &str) {
-    println!("Day 09 - Part 1");
+    part1(disk_map.clone());
+    part2(disk_map.clone());
+    strategies(&disk_map);
+    layout(&disk_map);
 
-    let mut blocks = Vec::<Option<isize>>::new();
-    for (i, c) in input.chars().enumerate() {
-        match i % 2 {
-            0 => {
-                blocks.extend(
-                    iter::repeat((i / 2) as isize)
-                        .take(c.to_digit(10).unwrap() as usize)
-                        .map(Some),
-                );
-            }
-            _ => {
-                blocks.extend(iter::repeat(None).take(c.to_digit(10).unwrap() as usize));
-            }
-        }
+    if let Some(script_path) = env::args().skip(1).find(|arg| !arg.starts_with("--")) {
+        simulate(disk_map.clone(), &script_path);
     }
+}
 
-    let mut left_pointer = 0;
-    let mut right_pointer = blocks.len() - 1;
-    while left_pointer < right_pointer {
-        if blocks[left_pointer].is_none() {
-            while blocks[right_pointer].is_none() {
-                right_pointer -= 1;
-            }
-
-            blocks[left_pointer] = blocks[right_pointer];
-            blocks[right_pointer] = None;
-            right_pointer -= 1;
-        }
-
-        left_pointer += 1;
-    }
+fn part1(mut disk_map: DiskMap) {
+    println!("Day 09 - Part 1");
 
-    let mut checksum = 0;
-    left_pointer = 0;
-    while let Some(block_id) = blocks[left_pointer] {
-        checksum += left_pointer * block_id as usize;
-        left_pointer += 1;
-    }
+    BlockLevel.compact(&mut disk_map);
 
-    println!("Total checksum: {}", checksum);
+    println!("Total checksum: {}", disk_map.checksum());
 }
 
-#[derive(Clone)]
-struct Block {
-    contents: Vec<Option<isize>>,
-}
+fn part2(mut disk_map: DiskMap) {
+    println!("Day 09 - Part 2");
 
-impl Block {
-    fn new(id: isize, size: usize) -> Self {
-        Block {
-            contents: iter::repeat(Some(id)).take(size).collect(),
-        }
-    }
+    FirstFit.compact(&mut disk_map);
 
-    fn new_empty(size: usize) -> Self {
-        Block {
-            contents: iter::repeat(None).take(size).collect(),
-        }
-    }
+    println!("Total checksum: {}", disk_map.checksum());
+}
 
-    fn free_space(&self) -> usize {
-        self.contents.iter().filter(|c| c.is_none()).count()
-    }
+fn strategies(disk_map: &DiskMap) {
+    println!("Day 09 - Strategies");
 
-    fn size(&self) -> usize {
-        self.contents.iter().filter(|c| c.is_some()).count()
-    }
+    let strategies: Vec<Box<dyn AllocationStrategy>> = vec![
+        Box::new(BlockLevel),
+        Box::new(FirstFit),
+        Box::new(BestFit),
+        Box::new(WorstFit),
+        Box::new(NextFit::new()),
+    ];
 
-    fn add_block(&mut self, block: Block) {
-        for c in block.contents.iter().filter(|c| c.is_some()) {
-            for i in 0..self.contents.len() {
-                if self.contents[i].is_none() {
-                    self.contents[i] = *c;
-                    break;
-                }
-            }
-        }
-    }
+    for mut strategy in strategies {
+        let mut compacted = disk_map.clone();
+        let metrics = strategy.compact(&mut compacted);
 
-    fn clear(&mut self) {
-        self.contents = iter::repeat(None).take(self.contents.len()).collect();
+        println!("{}: {}", strategy.name(), metrics);
     }
 }
 
-impl fmt::Debug for Block {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        for c in self.contents.iter() {
-            match c {
-                Some(id) => write!(f, "{}", id)?,
-                None => write!(f, ".")?,
-            }
-        }
+fn layout(disk_map: &DiskMap) {
+    println!("Day 09 - Layout");
 
-        Ok(())
-    }
-}
-
-fn part2(input: &str) {
-    println!("Day 09 - Part 2");
+    let mut compacted = disk_map.clone();
+    FirstFit.compact(&mut compacted);
 
-    let mut blocks = Vec::<Block>::new();
-    for (i, c) in input.chars().enumerate() {
-        match i % 2 {
-            0 => {
-                blocks.push(Block::new(
-                    (i / 2) as isize,
-                    c.to_digit(10).unwrap() as usize,
-                ));
-            }
-            _ => {
-                blocks.push(Block::new_empty(c.to_digit(10).unwrap() as usize));
-            }
-        }
+    fs::write("day09/layout.txt", render_columns(&compacted, 20)).unwrap();
+    fs::write("day09/compacted.txt", compacted.to_dense()).unwrap();
+    if env::args().any(|arg| arg == "--render") {
+        print!("{}", render_ansi(&compacted, 80));
     }
+}
 
-    let mut right_pointer = blocks.len() - 1;
-    while right_pointer > 0 {
-        let mut left_pointer = 0;
-        while blocks[right_pointer].size() > blocks[left_pointer].free_space() {
-            left_pointer += 1;
-        }
+fn simulate(mut disk_map: DiskMap, script_path: &str) {
+    println!("Day 09 - Simulation");
 
-        if left_pointer < right_pointer {
-            let block_to_move = blocks[right_pointer].clone();
-            blocks[left_pointer].add_block(block_to_move);
-            blocks[right_pointer].clear();
+    for line in fs::read_to_string(script_path).unwrap().lines() {
+        if line.trim().is_empty() {
+            continue;
         }
 
-        right_pointer -= 1;
-    }
+        let result = line
+            .parse::<Operation>()
+            .and_then(|operation| apply(&mut disk_map, &operation));
 
-    let mut checksum = 0;
-    let mut position = 0;
-    for block in blocks {
-        for c in block.contents.iter() {
-            if c.is_some() {
-                checksum += position * c.unwrap() as usize;
+        match result {
+            Ok(details) if details.is_empty() => {
+                println!("{}: checksum {}", line, disk_map.checksum())
             }
-            position += 1;
+            Ok(details) => println!("{}: {}, checksum {}", line, details, disk_map.checksum()),
+            Err(error) => println!("{}: {}", line, error),
         }
     }
-    println!("Total checksum: {}", checksum);
 }
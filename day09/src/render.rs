@@ -0,0 +1,53 @@
+use crate::disk_map::DiskMap;
+
+const ANSI_COLORS: [&str; 6] = ["31", "32", "33", "34", "35", "36"];
+const ANSI_DIM: &str = "2";
+const GLYPHS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+// One glyph per block, coloured by file. Glyph and colour together tell apart
+// 372 consecutive file ids, enough to eyeball a layout but not to read ids
+// back; `render_columns` is the exact format.
+pub fn render_ansi(disk_map: &DiskMap, width: usize) -> String {
+    let mut output = String::new();
+    for row in disk_map.blocks().chunks(width) {
+        for block in row {
+            match block {
+                Some(id) => output.push_str(&format!(
+                    "\x1b[{}m{}\x1b[0m",
+                    ANSI_COLORS[id / GLYPHS.len() % ANSI_COLORS.len()],
+                    GLYPHS[id % GLYPHS.len()] as char
+                )),
+                None => output.push_str(&format!("\x1b[{}m.\x1b[0m", ANSI_DIM)),
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+// Full file ids in fixed-width columns, `width` blocks per row, each row
+// prefixed with the position of its first block
+pub fn render_columns(disk_map: &DiskMap, width: usize) -> String {
+    let blocks = disk_map.blocks();
+    let id_width = blocks
+        .iter()
+        .flatten()
+        .max()
+        .map_or(1, |id| id.to_string().len());
+    let position_width = blocks.len().to_string().len();
+
+    let mut output = String::new();
+    for (i, row) in blocks.chunks(width).enumerate() {
+        output.push_str(&format!("{:>w$}:", i * width, w = position_width));
+        for block in row {
+            match block {
+                Some(id) => output.push_str(&format!(" {:>w$}", id, w = id_width)),
+                None => output.push_str(&format!(" {:>w$}", ".", w = id_width)),
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
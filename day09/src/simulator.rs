@@ -0,0 +1,80 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::{allocation::strategy_by_name, disk_map::DiskMap};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    Create(usize),
+    Delete(usize),
+    Append { file_id: usize, len: usize },
+    Compact(String),
+}
+
+impl FromStr for Operation {
+    type Err = OperationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || OperationError::Invalid(s.to_owned());
+        let number = |word: Option<&str>| word.and_then(|w| w.parse().ok()).ok_or_else(invalid);
+
+        let mut words = s.split_whitespace();
+        let operation = match words.next() {
+            Some("create") => Operation::Create(number(words.next())?),
+            Some("delete") => Operation::Delete(number(words.next())?),
+            Some("append") => Operation::Append {
+                file_id: number(words.next())?,
+                len: number(words.next())?,
+            },
+            Some("compact") => Operation::Compact(words.next().ok_or_else(invalid)?.to_owned()),
+            _ => return Err(invalid()),
+        };
+
+        if words.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(operation)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OperationError {
+    Invalid(String),
+    UnknownFile(usize),
+    UnknownStrategy(String),
+}
+
+impl Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperationError::Invalid(line) => write!(f, "invalid operation `{}`", line),
+            OperationError::UnknownFile(file_id) => write!(f, "no file with id {}", file_id),
+            OperationError::UnknownStrategy(name) => write!(f, "unknown strategy `{}`", name),
+        }
+    }
+}
+
+// Applies one operation to the disk. Creating a file reports its new id, and
+// compacting reports the strategy's metrics.
+pub fn apply(disk_map: &mut DiskMap, operation: &Operation) -> Result<String, OperationError> {
+    match operation {
+        Operation::Create(len) => Ok(format!("file {}", disk_map.create(*len))),
+        Operation::Delete(file_id) => disk_map
+            .delete(*file_id)
+            .then(String::new)
+            .ok_or(OperationError::UnknownFile(*file_id)),
+        Operation::Append { file_id, len } => disk_map
+            .append(*file_id, *len)
+            .then(String::new)
+            .ok_or(OperationError::UnknownFile(*file_id)),
+        Operation::Compact(name) => {
+            let mut strategy = strategy_by_name(name)
+                .ok_or_else(|| OperationError::UnknownStrategy(name.clone()))?;
+
+            Ok(strategy.compact(disk_map).to_string())
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    fmt::{self, Display},
+    ops::RangeInclusive,
+    rc::Rc,
+};
+
+const WORD_BITS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverflowError {
+    pub position: (usize, usize),
+}
+
+impl Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "trail count overflowed at ({}, {})",
+            self.position.0, self.position.1
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrailCounts {
+    pub score: usize,
+    pub rating: u128,
+}
+
+// Bitset over peak indexes that only stores the words between its lowest and
+// highest member
+#[derive(Debug, Clone)]
+struct PeakSet {
+    first_word: usize,
+    words: Vec<u64>,
+}
+
+impl PeakSet {
+    fn single(peak: usize) -> Self {
+        Self {
+            first_word: peak / WORD_BITS,
+            words: vec![1 << (peak % WORD_BITS)],
+        }
+    }
+
+    fn union(sets: &[&Rc<PeakSet>]) -> Self {
+        let first_word = sets.iter().map(|s| s.first_word).min().unwrap();
+        let end_word = sets
+            .iter()
+            .map(|s| s.first_word + s.words.len())
+            .max()
+            .unwrap();
+
+        let mut words = vec![0; end_word - first_word];
+        for set in sets {
+            let offset = set.first_word - first_word;
+            for (word, &other) in words[offset..].iter_mut().zip(&set.words) {
+                *word |= other;
+            }
+        }
+
+        Self { first_word, words }
+    }
+
+    fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+pub struct TopoMap {
+    heights: Vec<u32>,
+    width: usize,
+}
+
+impl From<&str> for TopoMap {
+    fn from(s: &str) -> Self {
+        let width = s.lines().next().map_or(0, str::len);
+        let heights = s
+            .lines()
+            .flat_map(|line| line.chars().map(|c| c.to_digit(10).unwrap()))
+            .collect();
+
+        Self { heights, width }
+    }
+}
+
+impl TopoMap {
+    fn position(&self, cell: usize) -> (usize, usize) {
+        (cell % self.width, cell / self.width)
+    }
+
+    fn neighbours(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = self.position(cell);
+        let height = self.heights.len() / self.width;
+
+        [(0, -1), (0, 1), (1, 0), (-1, 0)]
+            .into_iter()
+            .filter_map(move |(i, j)| {
+                let x = x.checked_add_signed(i).filter(|&x| x < self.width)?;
+                let y = y.checked_add_signed(j).filter(|&y| y < height)?;
+                Some(y * self.width + x)
+            })
+    }
+
+    // Numbers the peaks separately within each group of cells joined by
+    // single height steps, in reading order, so the peak sets a cell can hold
+    // only span the nearby peaks of its own group
+    fn peak_indexes(&self, range: &RangeInclusive<u32>) -> Vec<usize> {
+        let mut peak_indexes = vec![0; self.heights.len()];
+        let mut visited = vec![false; self.heights.len()];
+        let mut peaks = Vec::new();
+
+        for cell in 0..self.heights.len() {
+            if visited[cell] || !range.contains(&self.heights[cell]) {
+                continue;
+            }
+
+            visited[cell] = true;
+            let mut pending = vec![cell];
+            while let Some(current) = pending.pop() {
+                if self.heights[current] == *range.end() {
+                    peaks.push(current);
+                }
+
+                for neighbour in self.neighbours(current) {
+                    if !visited[neighbour]
+                        && range.contains(&self.heights[neighbour])
+                        && self.heights[neighbour].abs_diff(self.heights[current]) == 1
+                    {
+                        visited[neighbour] = true;
+                        pending.push(neighbour);
+                    }
+                }
+            }
+
+            peaks.sort_unstable();
+            for (i, peak) in peaks.drain(..).enumerate() {
+                peak_indexes[peak] = i;
+            }
+        }
+
+        peak_indexes
+    }
+
+    // Works down from the peaks one height at a time. Every cell gets the
+    // number of trails from it to a peak and the set of peaks it reaches, built
+    // from its neighbours one step up, so only two layers are live at once.
+    pub fn trail_counts(&self, range: RangeInclusive<u32>) -> Result<TrailCounts, OverflowError> {
+        let (&start, &end) = (range.start(), range.end());
+        if start > end || self.width == 0 {
+            return Ok(TrailCounts {
+                score: 0,
+                rating: 0,
+            });
+        }
+
+        let mut layers = vec![Vec::new(); (end - start) as usize + 1];
+        for (cell, &height) in self.heights.iter().enumerate() {
+            if range.contains(&height) {
+                layers[(height - start) as usize].push(cell);
+            }
+        }
+
+        let peak_indexes = self.peak_indexes(&range);
+        let mut ratings = vec![0u128; self.heights.len()];
+        let mut reachable: Vec<Option<Rc<PeakSet>>> = vec![None; self.heights.len()];
+        for &peak in &layers[layers.len() - 1] {
+            ratings[peak] = 1;
+            reachable[peak] = Some(Rc::new(PeakSet::single(peak_indexes[peak])));
+        }
+
+        for layer in (0..layers.len() - 1).rev() {
+            let next_height = start + layer as u32 + 1;
+
+            for &cell in &layers[layer] {
+                let mut rating: u128 = 0;
+                let mut contributing = Vec::new();
+                for neighbour in self.neighbours(cell) {
+                    if self.heights[neighbour] != next_height {
+                        continue;
+                    }
+
+                    rating = rating
+                        .checked_add(ratings[neighbour])
+                        .ok_or(OverflowError {
+                            position: self.position(cell),
+                        })?;
+                    if let Some(peaks) = &reachable[neighbour] {
+                        contributing.push(peaks);
+                    }
+                }
+
+                ratings[cell] = rating;
+                // Cells reaching no peak get no set, and a single contributing
+                // neighbour's set is shared rather than copied
+                reachable[cell] = match contributing.as_slice() {
+                    [] => None,
+                    [peaks] => Some(Rc::clone(peaks)),
+                    _ => Some(Rc::new(PeakSet::union(&contributing))),
+                };
+            }
+
+            for &cell in &layers[layer + 1] {
+                reachable[cell] = None;
+            }
+        }
+
+        let mut counts = TrailCounts {
+            score: 0,
+            rating: 0,
+        };
+        for &trailhead in &layers[0] {
+            counts.score += reachable[trailhead].as_ref().map_or(0, |peaks| peaks.len());
+            counts.rating = counts
+                .rating
+                .checked_add(ratings[trailhead])
+                .ok_or(OverflowError {
+                    position: self.position(trailhead),
+                })?;
+        }
+
+        Ok(counts)
+    }
+}